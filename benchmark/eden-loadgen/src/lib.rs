@@ -0,0 +1,306 @@
+use std::io;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use cacophony::scenario::Scenario;
+use cacophony::{PhaseResult, ScenarioResult};
+
+/// Side-by-side result of running one scenario against a raw Redis target and
+/// an Eden interlay port.
+#[derive(Serialize)]
+pub struct ComparisonReport {
+    pub scenario: String,
+    pub baseline_target: String,
+    pub candidate_target: String,
+    pub phases: Vec<PhaseComparison>,
+    pub gates: Vec<GateResult>,
+    /// Phases whose baseline run had zero throughput. There is nothing to
+    /// compare the candidate against, so any entry fails the comparison.
+    pub inconclusive: Vec<String>,
+    pub passed: bool,
+    pub baseline: ScenarioResult,
+    pub candidate: ScenarioResult,
+}
+
+/// Per-phase deltas between the baseline and candidate runs.
+///
+/// Overheads are `candidate - baseline`, so positive values mean the candidate
+/// (usually the interlay) is slower. Percentages are `None` when the baseline
+/// value is zero.
+#[derive(Serialize)]
+pub struct PhaseComparison {
+    pub name: String,
+    pub baseline: TargetStats,
+    pub candidate: TargetStats,
+    pub throughput_change_pct: Option<f64>,
+    pub error_rate_delta: f64,
+    pub p50_overhead_us: i64,
+    pub p99_overhead_us: i64,
+    pub p999_overhead_us: i64,
+    pub p99_overhead_pct: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct TargetStats {
+    pub throughput: f64,
+    pub completed: u64,
+    pub errors: u64,
+    pub shed: u64,
+    pub error_rate: f64,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    pub p999_us: u64,
+}
+
+impl TargetStats {
+    fn from_phase(phase: &PhaseResult) -> Self {
+        let summary = &phase.summary;
+        let attempted = summary.completed + summary.errors;
+        let throughput = if phase.elapsed_secs > 0.0 {
+            summary.completed as f64 / phase.elapsed_secs
+        } else {
+            0.0
+        };
+        let error_rate = if attempted > 0 {
+            summary.errors as f64 / attempted as f64
+        } else {
+            0.0
+        };
+
+        Self {
+            throughput,
+            completed: summary.completed,
+            errors: summary.errors,
+            shed: phase.shed,
+            error_rate,
+            p50_us: summary.service_latency_us.p50,
+            p95_us: summary.service_latency_us.p95,
+            p99_us: summary.service_latency_us.p99,
+            p999_us: summary.service_latency_us.p999,
+        }
+    }
+}
+
+/// Regression limits applied to every phase. Unset limits are not checked.
+#[derive(Debug, Clone, Default)]
+pub struct Thresholds {
+    pub max_p99_overhead_us: Option<u64>,
+    pub max_p99_overhead_pct: Option<f64>,
+    pub max_throughput_drop_pct: Option<f64>,
+    pub max_error_rate: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct GateResult {
+    pub phase: String,
+    pub gate: &'static str,
+    pub limit: f64,
+    /// `None` when the baseline was zero; the gate then fails.
+    pub observed: Option<f64>,
+    pub passed: bool,
+}
+
+/// Run `scenario` against `baseline` and then `candidate`, pausing for
+/// `cooldown` in between so the second run does not inherit the first run's
+/// queues, and compare the two.
+pub async fn run_comparison(
+    scenario: &Scenario,
+    baseline: &str,
+    candidate: &str,
+    loadgen_shards: usize,
+    cooldown: Duration,
+    thresholds: &Thresholds,
+) -> Result<ComparisonReport, io::Error> {
+    eprintln!("eden-loadgen: baseline run against {baseline}");
+    let baseline_result = cacophony::run_scenario_with_shards(scenario, baseline, loadgen_shards).await?;
+
+    if !cooldown.is_zero() {
+        eprintln!("eden-loadgen: cooling down for {:.1}s", cooldown.as_secs_f64());
+        tokio::time::sleep(cooldown).await;
+    }
+
+    eprintln!("eden-loadgen: candidate run against {candidate}");
+    let candidate_result = cacophony::run_scenario_with_shards(scenario, candidate, loadgen_shards).await?;
+
+    Ok(compare(baseline_result, candidate_result, thresholds))
+}
+
+/// Parse a `--cooldown` value in the scenario duration formats: `Ns`, `Nm`
+/// or `Nh`, e.g. "5s" or "1.5m".
+pub fn parse_cooldown(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (value, unit_secs) = if let Some(secs) = s.strip_suffix('s') {
+        (secs, 1.0)
+    } else if let Some(mins) = s.strip_suffix('m') {
+        (mins, 60.0)
+    } else if let Some(hours) = s.strip_suffix('h') {
+        (hours, 3600.0)
+    } else {
+        return Err(format!("unsupported duration `{s}` (expected Ns, Nm, or Nh)"));
+    };
+    value
+        .parse::<f64>()
+        .ok()
+        .and_then(|value| Duration::try_from_secs_f64(value * unit_secs).ok())
+        .ok_or_else(|| format!("invalid duration `{s}`"))
+}
+
+/// Build a comparison report from two runs of the same scenario.
+pub fn compare(baseline: ScenarioResult, candidate: ScenarioResult, thresholds: &Thresholds) -> ComparisonReport {
+    let phases: Vec<PhaseComparison> = baseline.phases.iter().zip(&candidate.phases).map(|(b, c)| compare_phase(b, c)).collect();
+    let gates: Vec<GateResult> = phases.iter().flat_map(|phase| evaluate_gates(phase, thresholds)).collect();
+    let inconclusive = inconclusive_phases(&phases);
+    let passed = inconclusive.is_empty() && gates.iter().all(|gate| gate.passed);
+
+    ComparisonReport {
+        scenario: baseline.scenario.clone(),
+        baseline_target: baseline.target.clone(),
+        candidate_target: candidate.target.clone(),
+        phases,
+        gates,
+        inconclusive,
+        passed,
+        baseline,
+        candidate,
+    }
+}
+
+fn compare_phase(baseline: &PhaseResult, candidate: &PhaseResult) -> PhaseComparison {
+    let b = TargetStats::from_phase(baseline);
+    let c = TargetStats::from_phase(candidate);
+
+    PhaseComparison {
+        name: baseline.name.clone(),
+        throughput_change_pct: percent_change(b.throughput, c.throughput),
+        error_rate_delta: c.error_rate - b.error_rate,
+        p50_overhead_us: c.p50_us as i64 - b.p50_us as i64,
+        p99_overhead_us: c.p99_us as i64 - b.p99_us as i64,
+        p999_overhead_us: c.p999_us as i64 - b.p999_us as i64,
+        p99_overhead_pct: percent_change(b.p99_us as f64, c.p99_us as f64),
+        baseline: b,
+        candidate: c,
+    }
+}
+
+fn evaluate_gates(phase: &PhaseComparison, thresholds: &Thresholds) -> Vec<GateResult> {
+    let mut gates = Vec::new();
+    let mut push = |gate: &'static str, limit: f64, observed: Option<f64>| {
+        gates.push(GateResult {
+            phase: phase.name.clone(),
+            gate,
+            limit,
+            observed,
+            passed: observed.is_some_and(|observed| observed <= limit),
+        });
+    };
+
+    if let Some(limit) = thresholds.max_p99_overhead_us {
+        push("max_p99_overhead_us", limit as f64, Some(phase.p99_overhead_us as f64));
+    }
+    if let Some(limit) = thresholds.max_p99_overhead_pct {
+        push("max_p99_overhead_pct", limit, phase.p99_overhead_pct);
+    }
+    if let Some(limit) = thresholds.max_throughput_drop_pct {
+        push("max_throughput_drop_pct", limit, phase.throughput_change_pct.map(|change| -change));
+    }
+    if let Some(limit) = thresholds.max_error_rate {
+        push("max_error_rate", limit, Some(phase.candidate.error_rate));
+    }
+
+    gates
+}
+
+fn inconclusive_phases(phases: &[PhaseComparison]) -> Vec<String> {
+    phases.iter().filter(|phase| phase.baseline.throughput == 0.0).map(|phase| phase.name.clone()).collect()
+}
+
+fn percent_change(before: f64, after: f64) -> Option<f64> {
+    (before != 0.0).then(|| (after - before) / before * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(throughput: f64, p99_us: u64, error_rate: f64) -> TargetStats {
+        TargetStats {
+            throughput,
+            completed: 0,
+            errors: 0,
+            shed: 0,
+            error_rate,
+            p50_us: 0,
+            p95_us: 0,
+            p99_us,
+            p999_us: 0,
+        }
+    }
+
+    fn phase(baseline: TargetStats, candidate: TargetStats) -> PhaseComparison {
+        PhaseComparison {
+            name: "steady".to_string(),
+            throughput_change_pct: percent_change(baseline.throughput, candidate.throughput),
+            error_rate_delta: candidate.error_rate - baseline.error_rate,
+            p50_overhead_us: 0,
+            p99_overhead_us: candidate.p99_us as i64 - baseline.p99_us as i64,
+            p999_overhead_us: 0,
+            p99_overhead_pct: percent_change(baseline.p99_us as f64, candidate.p99_us as f64),
+            baseline,
+            candidate,
+        }
+    }
+
+    #[test]
+    fn zero_baseline_is_inconclusive_and_fails() {
+        assert_eq!(percent_change(0.0, 10.0), None);
+        assert_eq!(percent_change(200.0, 150.0), Some(-25.0));
+
+        let comparison = phase(stats(0.0, 0, 1.0), stats(9_800.0, 500, 0.0));
+        let thresholds = Thresholds { max_throughput_drop_pct: Some(5.0), ..Thresholds::default() };
+        let gates = evaluate_gates(&comparison, &thresholds);
+        assert_eq!(gates[0].observed, None);
+        assert!(!gates[0].passed);
+        assert_eq!(inconclusive_phases(&[comparison]), ["steady"]);
+    }
+
+    #[test]
+    fn cooldown_rejects_malformed_durations() {
+        assert_eq!(parse_cooldown("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_cooldown("1.5m"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_cooldown("0s"), Ok(Duration::ZERO));
+        for bad in ["0", "5", "abc", "xs", "-1s", "5ms"] {
+            assert!(parse_cooldown(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn gates_pass_within_limits() {
+        let comparison = phase(stats(10_000.0, 400, 0.0), stats(9_800.0, 500, 0.0));
+        let thresholds = Thresholds {
+            max_p99_overhead_us: Some(200),
+            max_p99_overhead_pct: Some(50.0),
+            max_throughput_drop_pct: Some(5.0),
+            max_error_rate: Some(0.001),
+        };
+
+        let gates = evaluate_gates(&comparison, &thresholds);
+        assert_eq!(gates.len(), 4);
+        assert!(gates.iter().all(|gate| gate.passed));
+    }
+
+    #[test]
+    fn gates_flag_latency_and_throughput_regressions() {
+        let comparison = phase(stats(10_000.0, 400, 0.0), stats(8_000.0, 1_000, 0.0));
+        let thresholds = Thresholds {
+            max_p99_overhead_us: Some(200),
+            max_throughput_drop_pct: Some(5.0),
+            ..Thresholds::default()
+        };
+
+        let failed: Vec<&str> =
+            evaluate_gates(&comparison, &thresholds).into_iter().filter(|gate| !gate.passed).map(|gate| gate.gate).collect();
+        assert_eq!(failed, vec!["max_p99_overhead_us", "max_throughput_drop_pct"]);
+    }
+}
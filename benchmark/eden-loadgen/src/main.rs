@@ -0,0 +1,128 @@
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+use clap::Parser;
+
+use cacophony::scenario::Scenario;
+use eden_loadgen::{ComparisonReport, Thresholds, parse_cooldown};
+
+/// Exit code used when the runs complete but a regression gate fails.
+const GATE_FAILURE_EXIT_CODE: i32 = 2;
+
+#[derive(Parser)]
+#[command(name = "eden-loadgen", about = "Compare Redis latency and throughput with and without an Eden interlay")]
+struct Cli {
+    /// Path to a cacophony scenario TOML file.
+    #[arg(long)]
+    scenario: String,
+
+    /// Raw Redis target (host:port) used as the baseline.
+    #[arg(long)]
+    baseline: String,
+
+    /// Eden interlay port (host:port) compared against the baseline.
+    #[arg(long)]
+    candidate: String,
+
+    /// Number of parallel load-generator shards to run per phase.
+    #[arg(long, default_value_t = 1)]
+    loadgen_shards: usize,
+
+    /// Pause between the baseline and candidate runs (e.g. "5s", "1m").
+    #[arg(long, default_value = "5s", value_parser = parse_cooldown)]
+    cooldown: Duration,
+
+    /// Fail when any phase's p99 overhead exceeds this many microseconds.
+    #[arg(long)]
+    max_p99_overhead_us: Option<u64>,
+
+    /// Fail when any phase's p99 overhead exceeds this percentage of the baseline.
+    #[arg(long)]
+    max_p99_overhead_pct: Option<f64>,
+
+    /// Fail when any phase's throughput drops by more than this percentage.
+    #[arg(long)]
+    max_throughput_drop_pct: Option<f64>,
+
+    /// Fail when any phase's candidate error rate (0.0-1.0) exceeds this value.
+    #[arg(long)]
+    max_error_rate: Option<f64>,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    let toml_str = match fs::read_to_string(&cli.scenario) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("error: failed to read scenario file '{}': {e}", cli.scenario);
+            process::exit(1);
+        }
+    };
+
+    let scenario: Scenario = match toml::from_str(&toml_str) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("error: failed to parse scenario file '{}': {e}", cli.scenario);
+            process::exit(1);
+        }
+    };
+
+    if cli.loadgen_shards == 0 {
+        eprintln!("error: --loadgen-shards must be > 0");
+        process::exit(1);
+    }
+
+    let thresholds = Thresholds {
+        max_p99_overhead_us: cli.max_p99_overhead_us,
+        max_p99_overhead_pct: cli.max_p99_overhead_pct,
+        max_throughput_drop_pct: cli.max_throughput_drop_pct,
+        max_error_rate: cli.max_error_rate,
+    };
+    let report =
+        match eden_loadgen::run_comparison(&scenario, &cli.baseline, &cli.candidate, cli.loadgen_shards, cli.cooldown, &thresholds).await {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!("error: {e}");
+                process::exit(1);
+            }
+        };
+
+    print_table(&report);
+    let json = serde_json::to_string_pretty(&report).expect("JSON serialization");
+    println!("{json}");
+
+    if !report.passed {
+        process::exit(GATE_FAILURE_EXIT_CODE);
+    }
+}
+
+fn print_table(report: &ComparisonReport) {
+    eprintln!();
+    eprintln!(
+        "{:<16} {:>12} {:>12} {:>9} {:>10} {:>10} {:>10}",
+        "phase", "base req/s", "cand req/s", "Δ tput", "base p99", "cand p99", "Δ p99"
+    );
+    for phase in &report.phases {
+        let throughput_change = phase.throughput_change_pct.map_or_else(|| "-".to_string(), |change| format!("{change:.1}%"));
+        eprintln!(
+            "{:<16} {:>12.0} {:>12.0} {:>9} {:>8}μs {:>8}μs {:>8}μs",
+            phase.name,
+            phase.baseline.throughput,
+            phase.candidate.throughput,
+            throughput_change,
+            phase.baseline.p99_us,
+            phase.candidate.p99_us,
+            phase.p99_overhead_us,
+        );
+    }
+    for phase in &report.inconclusive {
+        eprintln!("inconclusive: phase '{phase}' baseline had zero throughput");
+    }
+    for gate in report.gates.iter().filter(|gate| !gate.passed) {
+        let observed = gate.observed.map_or_else(|| "-".to_string(), |observed| format!("{observed:.2}"));
+        eprintln!("gate failed: phase '{}' {} observed={observed} limit={:.2}", gate.phase, gate.gate, gate.limit);
+    }
+}
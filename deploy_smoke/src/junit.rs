@@ -0,0 +1,148 @@
+use std::fmt::Write;
+
+use crate::report::{Outcome, SmokeReport};
+use crate::soak::{MIN_LEAK_SAMPLES, SoakReport};
+
+/// JUnit XML for CI: one test case per step, one per SLO and two for a soak,
+/// so a failed call, an exceeded latency budget or a leak shows up as a
+/// failed test.
+pub fn render(report: &SmokeReport) -> String {
+    let soak_cases = report.soak.as_ref().map(soak_cases).unwrap_or_default();
+    let failures = report.count(Outcome::Fail)
+        + report.slos.iter().filter(|slo| !slo.passed).count()
+        + soak_cases.iter().filter(|(_, failure)| failure.is_some()).count();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        out,
+        "<testsuite name=\"deploy-smoke\" tests=\"{}\" failures=\"{failures}\" skipped=\"{}\" time=\"{:.3}\">",
+        report.steps.len() + report.slos.len() + soak_cases.len(),
+        report.count(Outcome::Skip),
+        report.elapsed_secs
+    )
+    .expect("writing to a String");
+    writeln!(out, "  <properties>\n    <property name=\"url\" value=\"{}\"/>", escape(&report.url)).expect("writing to a String");
+    writeln!(out, "    <property name=\"org\" value=\"{}\"/>\n  </properties>", escape(&report.org)).expect("writing to a String");
+
+    for step in &report.steps {
+        let time = step.latency_ms.unwrap_or_default() / 1000.0;
+        let detail = escape(step.detail.as_deref().unwrap_or_default());
+        let head = format!("  <testcase classname=\"deploy-smoke.steps\" name=\"{}\" time=\"{time:.3}\"", escape(&step.name));
+        match step.outcome {
+            Outcome::Pass => writeln!(out, "{head}/>"),
+            Outcome::Fail => {
+                let summary = escape(step.detail.as_deref().unwrap_or_default().lines().next().unwrap_or_default());
+                writeln!(out, "{head}>\n    <failure message=\"{summary}\">{detail}</failure>\n  </testcase>")
+            }
+            Outcome::Skip => writeln!(out, "{head}>\n    <skipped message=\"{detail}\"/>\n  </testcase>"),
+        }
+        .expect("writing to a String");
+    }
+
+    for slo in &report.slos {
+        let head = format!("  <testcase classname=\"deploy-smoke.slo\" name=\"{}\" time=\"0.000\"", escape(&slo.name));
+        if slo.passed {
+            writeln!(out, "{head}/>").expect("writing to a String");
+            continue;
+        }
+        let message = match slo.measured_ms {
+            Some(ms) => format!("{} was {ms:.3}ms; budget {}ms", slo.name, slo.max_ms),
+            None => format!("{} was not measured; budget {}ms", slo.name, slo.max_ms),
+        };
+        writeln!(out, "{head}>\n    <failure message=\"{}\"/>\n  </testcase>", escape(&message)).expect("writing to a String");
+    }
+
+    for (name, failure) in &soak_cases {
+        let head = format!("  <testcase classname=\"deploy-smoke.soak\" name=\"{name}\" time=\"0.000\"");
+        match failure {
+            None => writeln!(out, "{head}/>"),
+            Some(message) => writeln!(out, "{head}>\n    <failure message=\"{}\"/>\n  </testcase>", escape(message)),
+        }
+        .expect("writing to a String");
+    }
+    out.push_str("</testsuite>\n");
+    out
+}
+
+/// `soak.operations` and `soak.leaks`, with a failure message when they failed.
+/// Too few samples to look for leaks also fails `soak.leaks`.
+fn soak_cases(soak: &SoakReport) -> Vec<(&'static str, Option<String>)> {
+    let operations = (soak.failed_operations > 0).then(|| {
+        let last_error = soak.last_error.as_deref().unwrap_or_default();
+        format!("{} of {} operations failed; last error: {last_error}", soak.failed_operations, soak.operations)
+    });
+    let leaks = (!soak.leaks.is_empty()).then(|| {
+        let leaks: Vec<String> = soak
+            .leaks
+            .iter()
+            .map(|leak| format!("{} grew {} -> {} (+{:.1}%)", leak.metric, leak.first, leak.last, leak.growth_pct))
+            .collect();
+        leaks.join("; ")
+    });
+    let leaks = leaks.or_else(|| {
+        soak.too_few_samples()
+            .then(|| format!("only {} samples; {MIN_LEAK_SAMPLES} are needed to check for leaks", soak.samples.len()))
+    });
+    vec![("soak.operations", operations), ("soak.leaks", leaks)]
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' => out.push_str("&#10;"),
+            // Control characters other than tab are not allowed in XML 1.0.
+            c if c.is_control() && c != '\t' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::StepResult;
+    use crate::slo::SloResult;
+
+    #[test]
+    fn failed_steps_and_slos_become_failed_cases() {
+        let step = |name: &str, outcome, detail: Option<&str>| StepResult {
+            name: name.to_string(),
+            outcome,
+            latency_ms: Some(12.0),
+            status: None,
+            detail: detail.map(str::to_string),
+        };
+        let report = SmokeReport {
+            url: "http://eden".to_string(),
+            org: "smoke".to_string(),
+            steps: vec![
+                step("org.create", Outcome::Pass, None),
+                step("endpoint.get", Outcome::Fail, Some("GET returned 500: <html>\nbody")),
+                step("interlay.create", Outcome::Skip, Some("needs endpoint.create")),
+            ],
+            api_latency: None,
+            proxy_overhead: None,
+            slos: vec![SloResult {
+                name: "api.p95".to_string(),
+                max_ms: 200.0,
+                measured_ms: Some(250.0),
+                passed: false,
+            }],
+            soak: None,
+            elapsed_secs: 1.0,
+            passed: false,
+        };
+        let xml = render(&report);
+        assert!(xml.contains("tests=\"4\" failures=\"2\" skipped=\"1\""));
+        assert!(xml.contains("name=\"org.create\" time=\"0.012\"/>"));
+        assert!(xml.contains("<failure message=\"GET returned 500: &lt;html&gt;\">GET returned 500: &lt;html&gt;&#10;body</failure>"));
+        assert!(xml.contains("<skipped message=\"needs endpoint.create\"/>"));
+        assert!(xml.contains("<failure message=\"api.p95 was 250.000ms; budget 200ms\"/>"));
+    }
+}
@@ -0,0 +1,99 @@
+use std::io;
+use std::time::Instant;
+
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const PING: &[u8] = b"*1\r\n$4\r\nPING\r\n";
+
+/// Latency distribution of a set of samples, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Percentiles {
+    /// `None` when there are no samples.
+    pub fn of(samples: &[f64]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        Some(Self {
+            p50: nearest_rank(&sorted, 0.50)?,
+            p95: nearest_rank(&sorted, 0.95)?,
+            p99: nearest_rank(&sorted, 0.99)?,
+            max: *sorted.last()?,
+        })
+    }
+
+    /// Percentile by name: `p50`, `p95`, `p99` or `max`.
+    pub fn get(&self, name: &str) -> Option<f64> {
+        match name {
+            "p50" => Some(self.p50),
+            "p95" => Some(self.p95),
+            "p99" => Some(self.p99),
+            "max" => Some(self.max),
+            _ => None,
+        }
+    }
+
+    /// Per-percentile difference, e.g. the latency an interlay adds over a
+    /// direct connection. Tail samples are noisy, so this can be negative.
+    pub fn minus(&self, base: &Percentiles) -> Percentiles {
+        Percentiles {
+            p50: self.p50 - base.p50,
+            p95: self.p95 - base.p95,
+            p99: self.p99 - base.p99,
+            max: self.max - base.max,
+        }
+    }
+}
+
+fn nearest_rank(sorted: &[f64], quantile: f64) -> Option<f64> {
+    let rank = (quantile * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.saturating_sub(1)).copied()
+}
+
+/// Round-trip times of `samples` sequential `PING`s over one connection to a
+/// Redis endpoint or interlay, in milliseconds.
+pub async fn ping_samples(addr: &str, samples: usize) -> io::Result<Vec<f64>> {
+    let mut stream = TcpStream::connect(addr).await?;
+    stream.set_nodelay(true)?;
+    let mut buf = [0u8; 512];
+    let mut times = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        let started = Instant::now();
+        stream.write_all(PING).await?;
+        let mut reply = Vec::new();
+        while !reply.ends_with(b"\r\n") {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            reply.extend_from_slice(&buf[..n]);
+        }
+        times.push(started.elapsed().as_secs_f64() * 1000.0);
+        if !reply.starts_with(b"+") {
+            let reply = String::from_utf8_lossy(&reply);
+            return Err(io::Error::other(format!("PING to {addr} answered {}", reply.trim_end())));
+        }
+    }
+    Ok(times)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let samples: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+        let percentiles = Percentiles::of(&samples).expect("samples");
+        assert_eq!(percentiles, Percentiles { p50: 50.0, p95: 95.0, p99: 99.0, max: 100.0 });
+        assert_eq!(Percentiles::of(&[]), None);
+        assert_eq!(Percentiles::of(&[3.0]).map(|p| p.p99), Some(3.0));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod junit;
+pub mod latency;
+pub mod report;
+pub mod slo;
+pub mod smoke;
+pub mod soak;
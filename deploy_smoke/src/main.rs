@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{fs, process};
+
+use clap::Parser;
+use eden_client::profile;
+
+use deploy_smoke::junit;
+use deploy_smoke::report::{self, Outcome};
+use deploy_smoke::slo::Slo;
+use deploy_smoke::smoke::{self, SmokeConfig};
+use deploy_smoke::soak::{MIN_LEAK_SAMPLES, SoakConfig};
+
+/// Exit code used when the run completes but a step failed or an SLO was
+/// exceeded.
+const FAILURE_EXIT_CODE: i32 = 2;
+
+/// Smoke-test a freshly deployed Eden control plane end to end: organization
+/// creation, login, endpoint and interlay CRUD, and cleanup.
+#[derive(Parser)]
+#[command(name = "deploy-smoke")]
+struct Cli {
+    /// Profile from ~/.eden/config.toml supplying the URL, new-org token, TLS
+    /// and transport settings.
+    #[arg(long, env = "EDEN_PROFILE")]
+    profile: Option<String>,
+
+    /// Eden service root, e.g. https://eden.staging.example.com. Overrides
+    /// the profile.
+    #[arg(long, env = "EDEN_URL")]
+    url: Option<String>,
+
+    /// Organization to create; defaults to a unique smoke-<time>-<random> ID.
+    #[arg(long)]
+    org: Option<String>,
+
+    #[arg(long, default_value = "smoke-admin")]
+    admin_username: String,
+
+    /// Password for the organization's super admin; generated when omitted.
+    #[arg(long, env = "DEPLOY_SMOKE_PASSWORD", hide_env_values = true)]
+    admin_password: Option<String>,
+
+    /// Bearer token required by deployments that set EDEN_NEW_ORG_TOKEN.
+    /// Overrides the profile.
+    #[arg(long, env = "EDEN_NEW_ORG_TOKEN", hide_env_values = true)]
+    new_org_token: Option<String>,
+
+    /// Redis host:port registered as the test endpoint; the service must be
+    /// able to reach it.
+    #[arg(long, default_value = "127.0.0.1:6379")]
+    redis: String,
+
+    /// Leave the organization in place instead of deleting it.
+    #[arg(long)]
+    keep: bool,
+
+    /// Latency budget such as api.p95=200 or proxy.p99=2, in milliseconds;
+    /// repeatable. `proxy` is the overhead the interlay adds to a PING.
+    #[arg(long = "slo", value_name = "NAME=MS")]
+    slos: Vec<Slo>,
+
+    /// PINGs sent directly and through the interlay; 0 skips the measurement.
+    #[arg(long, default_value_t = 100)]
+    proxy_samples: usize,
+
+    /// Host the interlay listens on; defaults to the host of --url.
+    #[arg(long)]
+    interlay_host: Option<String>,
+
+    /// Also write the results as JUnit XML to this path.
+    #[arg(long)]
+    junit: Option<PathBuf>,
+
+    /// Keep traffic running through the interlay for this many hours before
+    /// cleanup, sampling memory and connection counts to detect leaks.
+    #[arg(long)]
+    soak_hours: Option<f64>,
+
+    /// Seconds between soak samples.
+    #[arg(long, default_value_t = 60, requires = "soak_hours")]
+    soak_sample_secs: u64,
+
+    /// Commands per second sent through the interlay during the soak.
+    #[arg(long, default_value_t = 500, requires = "soak_hours")]
+    soak_rate: u32,
+
+    /// Second Redis host:port to sample during the soak, e.g. a migration
+    /// target.
+    #[arg(long, requires = "soak_hours")]
+    soak_target_redis: Option<String>,
+
+    /// Percent growth over the first sample above which a metric that never
+    /// decreased is reported as a leak.
+    #[arg(long, default_value_t = 10.0, requires = "soak_hours")]
+    leak_tolerance_pct: f64,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let (name, profile) = match profile::load_profile(cli.profile.as_deref()) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    };
+    let Some(url) = cli.url.or(profile.url) else {
+        eprintln!("error: no API URL; pass --url or set `url` in profile `{name}`");
+        process::exit(1);
+    };
+    let mut config = SmokeConfig {
+        url,
+        org: cli.org.unwrap_or_else(SmokeConfig::generated_org),
+        admin_username: cli.admin_username,
+        admin_password: cli.admin_password.unwrap_or_else(SmokeConfig::generated_password),
+        new_org_token: cli.new_org_token.or(profile.new_org_token),
+        tls: profile.tls,
+        transport: profile.transport,
+        redis: cli.redis,
+        keep: cli.keep,
+        slos: cli.slos,
+        proxy_samples: cli.proxy_samples,
+        interlay_host: cli.interlay_host,
+        soak: None,
+    };
+    if let Some(hours) = cli.soak_hours {
+        let duration = match Duration::try_from_secs_f64(hours * 3600.0) {
+            Ok(duration) if cli.soak_sample_secs > 0 => duration,
+            _ => {
+                eprintln!("error: --soak-hours must be a non-negative number and --soak-sample-secs at least 1");
+                process::exit(1);
+            }
+        };
+        config.soak = Some(SoakConfig {
+            duration,
+            sample_interval: Duration::from_secs(cli.soak_sample_secs),
+            churn_rate: cli.soak_rate,
+            target_redis: cli.soak_target_redis,
+            leak_tolerance_pct: cli.leak_tolerance_pct,
+        });
+    }
+
+    eprintln!("deploy-smoke: url={} org={}", config.url, config.org);
+    let report = match smoke::run(&config).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    };
+
+    eprint!("\n{}", report::render_matrix(&report.steps));
+    for slo in &report.slos {
+        let measured = slo.measured_ms.map(|ms| format!("{ms:.3}ms")).unwrap_or_else(|| "not measured".to_string());
+        let result = if slo.passed { "PASS" } else { "FAIL" };
+        eprintln!("slo: {} {measured} (budget {}ms) {result}", slo.name, slo.max_ms);
+    }
+    if let Some(soak) = &report.soak {
+        eprintln!(
+            "soak: elapsed={:.0}s operations={} failed={} samples={} leaks={}",
+            soak.elapsed_secs,
+            soak.operations,
+            soak.failed_operations,
+            soak.samples.len(),
+            soak.leaks.len()
+        );
+        for leak in &soak.leaks {
+            eprintln!("leak: {} grew {} -> {} (+{:.1}%)", leak.metric, leak.first, leak.last, leak.growth_pct);
+        }
+        if soak.too_few_samples() {
+            eprintln!("soak: only {} samples; {MIN_LEAK_SAMPLES} are needed to check for leaks", soak.samples.len());
+        }
+    }
+    eprintln!(
+        "done: passed={} failed={} skipped={} elapsed={:.1}s",
+        report.count(Outcome::Pass),
+        report.count(Outcome::Fail),
+        report.count(Outcome::Skip),
+        report.elapsed_secs
+    );
+    eprintln!("result: {}", if report.passed { "PASS" } else { "FAIL" });
+    let json = serde_json::to_string_pretty(&report).expect("JSON serialization");
+    println!("{json}");
+    if let Some(path) = &cli.junit
+        && let Err(e) = fs::write(path, junit::render(&report))
+    {
+        eprintln!("error: writing {}: {e}", path.display());
+        process::exit(1);
+    }
+
+    if !report.passed {
+        process::exit(FAILURE_EXIT_CODE);
+    }
+}
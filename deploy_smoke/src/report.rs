@@ -0,0 +1,117 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::latency::Percentiles;
+use crate::slo::SloResult;
+use crate::soak::SoakReport;
+
+/// Characters of a step's detail shown in the matrix.
+const MAX_DETAIL: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// Not attempted because a step it depends on failed, or because the
+    /// service has no API for it.
+    Skip,
+}
+
+impl Outcome {
+    fn label(self) -> &'static str {
+        match self {
+            Outcome::Pass => "PASS",
+            Outcome::Fail => "FAIL",
+            Outcome::Skip => "SKIP",
+        }
+    }
+}
+
+/// One API call of the smoke run.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    pub name: String,
+    pub outcome: Outcome,
+    /// Wall time of the call, including retries after 429 responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    /// HTTP status of a failed call, when the service answered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SmokeReport {
+    pub url: String,
+    pub org: String,
+    pub steps: Vec<StepResult>,
+    /// Latency of the control-plane calls that passed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_latency: Option<Percentiles>,
+    /// Latency the interlay added to a `PING` over a direct connection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_overhead: Option<Percentiles>,
+    pub slos: Vec<SloResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soak: Option<SoakReport>,
+    pub elapsed_secs: f64,
+    /// True when no step failed, every SLO held and the soak, if any, saw no
+    /// failed operations or leaks. Skipped steps do not fail the run.
+    pub passed: bool,
+}
+
+impl SmokeReport {
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.steps.iter().filter(|step| step.outcome == outcome).count()
+    }
+}
+
+/// Aligned pass/fail matrix with one row per step.
+pub fn render_matrix(steps: &[StepResult]) -> String {
+    let width = steps.iter().map(|step| step.name.len()).max().unwrap_or(0).max("STEP".len());
+    let mut out = format!("{:<width$}  RESULT  {:>10}  DETAIL\n", "STEP", "LATENCY");
+    for step in steps {
+        let latency = step.latency_ms.map(|ms| format!("{ms:.1}ms")).unwrap_or_default();
+        // Error bodies can be whole HTML pages; the JSON report keeps them in full.
+        let detail: String =
+            step.detail.as_deref().unwrap_or_default().lines().next().unwrap_or_default().chars().take(MAX_DETAIL).collect();
+        let line = format!("{:<width$}  {:<6}  {latency:>10}  {detail}", step.name, step.outcome.label());
+        writeln!(out, "{}", line.trim_end()).expect("writing to a String");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_aligns_steps_and_omits_missing_latency() {
+        let steps = vec![
+            StepResult {
+                name: "org.create".to_string(),
+                outcome: Outcome::Pass,
+                latency_ms: Some(85.24),
+                status: None,
+                detail: None,
+            },
+            StepResult {
+                name: "endpoint.get".to_string(),
+                outcome: Outcome::Skip,
+                latency_ms: None,
+                status: None,
+                detail: Some("needs endpoint.create".to_string()),
+            },
+        ];
+        assert_eq!(
+            render_matrix(&steps),
+            "STEP          RESULT     LATENCY  DETAIL\n\
+             org.create    PASS        85.2ms\n\
+             endpoint.get  SKIP                needs endpoint.create\n"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::latency::Percentiles;
+
+/// Distribution an [`Slo`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Latency of the control-plane calls that passed.
+    Api,
+    /// Latency an interlay adds to a `PING` over a direct connection.
+    Proxy,
+}
+
+/// A latency budget such as `api.p95=200`: the named percentile must not
+/// exceed `max_ms` milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slo {
+    pub scope: Scope,
+    /// `p50`, `p95`, `p99` or `max`.
+    pub percentile: String,
+    pub max_ms: f64,
+}
+
+impl fmt::Display for Slo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scope = match self.scope {
+            Scope::Api => "api",
+            Scope::Proxy => "proxy",
+        };
+        write!(f, "{scope}.{}", self.percentile)
+    }
+}
+
+impl FromStr for Slo {
+    type Err = String;
+
+    /// Parse `<api|proxy>.<p50|p95|p99|max>=<ms>`, with an optional `ms` suffix.
+    fn from_str(text: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid SLO `{text}`; expected e.g. api.p95=200 or proxy.p99=2ms");
+        let (metric, limit) = text.split_once('=').ok_or_else(invalid)?;
+        let (scope, percentile) = metric.trim().split_once('.').ok_or_else(invalid)?;
+        let scope = match scope {
+            "api" => Scope::Api,
+            "proxy" => Scope::Proxy,
+            _ => return Err(invalid()),
+        };
+        if !matches!(percentile, "p50" | "p95" | "p99" | "max") {
+            return Err(invalid());
+        }
+        let limit = limit.trim();
+        let max_ms: f64 = limit.strip_suffix("ms").unwrap_or(limit).parse().map_err(|_| invalid())?;
+        if !max_ms.is_finite() || max_ms < 0.0 {
+            return Err(invalid());
+        }
+        Ok(Slo { scope, percentile: percentile.to_string(), max_ms })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SloResult {
+    /// e.g. `api.p95`.
+    pub name: String,
+    pub max_ms: f64,
+    /// Absent when the distribution was not measured, which fails the SLO.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub measured_ms: Option<f64>,
+    pub passed: bool,
+}
+
+/// Check each budget against the measured distributions.
+pub fn evaluate(slos: &[Slo], api: Option<&Percentiles>, proxy_overhead: Option<&Percentiles>) -> Vec<SloResult> {
+    slos.iter()
+        .map(|slo| {
+            let distribution = match slo.scope {
+                Scope::Api => api,
+                Scope::Proxy => proxy_overhead,
+            };
+            let measured_ms = distribution.and_then(|percentiles| percentiles.get(&slo.percentile));
+            SloResult {
+                name: slo.to_string(),
+                max_ms: slo.max_ms,
+                measured_ms,
+                passed: measured_ms.is_some_and(|ms| ms <= slo.max_ms),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_evaluates_budgets() {
+        let api: Slo = "api.p95=200".parse().expect("valid");
+        let proxy: Slo = "proxy.p99=2ms".parse().expect("valid");
+        assert_eq!(
+            proxy,
+            Slo {
+                scope: Scope::Proxy,
+                percentile: "p99".to_string(),
+                max_ms: 2.0
+            }
+        );
+        assert!("api.p90=1".parse::<Slo>().is_err());
+        assert!("db.p95=1".parse::<Slo>().is_err());
+        assert!("api.p95".parse::<Slo>().is_err());
+
+        let measured = Percentiles { p50: 40.0, p95: 180.0, p99: 250.0, max: 300.0 };
+        let results = evaluate(&[api, proxy], Some(&measured), None);
+        assert!(results[0].passed);
+        assert_eq!(results[0].measured_ms, Some(180.0));
+        assert_eq!((results[1].name.as_str(), results[1].passed, results[1].measured_ms), ("proxy.p99", false, None));
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use eden_client::tls::TlsConfig;
+use eden_client::transport::TransportConfig;
+use eden_client::types::{NewEndpoint, NewInterlay, NewOrganization, NewUser};
+use eden_client::{ClientError, EdenApiClient};
+use reqwest::Url;
+use serde_json::{Value, json};
+
+use crate::latency::{self, Percentiles};
+use crate::report::{Outcome, SmokeReport, StepResult};
+use crate::slo::{self, Slo};
+use crate::soak::{self, SoakConfig};
+
+/// Steps the service has no API for yet. They are listed in the matrix as
+/// skipped so a report shows what was not covered.
+const UNSUPPORTED_STEPS: &[&str] = &["migration.create", "migration.traffic", "migration.complete", "migration.rollback"];
+
+/// Steps that time Redis traffic rather than an API call; they are left out
+/// of the API latency distribution.
+const TRAFFIC_STEPS: &[&str] = &["proxy.ping", "soak"];
+
+#[derive(Debug, Clone)]
+pub struct SmokeConfig {
+    /// Eden service root, e.g. `https://eden.staging.example.com`.
+    pub url: String,
+    /// Organization created for the run; it is deleted at the end unless `keep`.
+    pub org: String,
+    pub admin_username: String,
+    pub admin_password: String,
+    /// Bearer token for deployments that set `EDEN_NEW_ORG_TOKEN`.
+    pub new_org_token: Option<String>,
+    pub tls: TlsConfig,
+    pub transport: TransportConfig,
+    /// Redis `host:port` registered as the test endpoint. The service
+    /// connects to it when the endpoint is created.
+    pub redis: String,
+    pub keep: bool,
+    /// Latency budgets checked at the end of the run.
+    pub slos: Vec<Slo>,
+    /// `PING`s sent directly to `redis` and through the interlay to measure
+    /// its overhead; 0 skips the measurement.
+    pub proxy_samples: usize,
+    /// Host the interlay listens on; defaults to the host of `url`.
+    pub interlay_host: Option<String>,
+    /// Keep traffic running through the interlay and watch for leaks before
+    /// cleaning up.
+    pub soak: Option<SoakConfig>,
+}
+
+impl SmokeConfig {
+    /// An organization ID that will not collide with earlier runs.
+    pub fn generated_org() -> String {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default();
+        format!("smoke-{secs}-{:06x}", random() & 0xff_ffff)
+    }
+
+    pub fn generated_password() -> String {
+        format!("Smoke-{:016x}!", random())
+    }
+}
+
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Runs steps in order and records their outcome. A step whose
+/// prerequisites did not pass is skipped without sending its request.
+#[derive(Debug, Default)]
+pub struct Runner {
+    steps: Vec<StepResult>,
+}
+
+impl Runner {
+    pub fn passed(&self, name: &str) -> bool {
+        self.steps.iter().any(|step| step.name == name && step.outcome == Outcome::Pass)
+    }
+
+    /// Time `call` as step `name`. `call` is only polled when every step in
+    /// `needs` passed.
+    pub async fn step<T>(&mut self, name: &str, needs: &[&str], call: impl Future<Output = Result<T, ClientError>>) -> Option<T> {
+        if let Some(missing) = needs.iter().find(|need| !self.passed(need)) {
+            self.skip(name, format!("needs {missing}"));
+            return None;
+        }
+        let started = Instant::now();
+        let result = call.await;
+        let latency_ms = Some(started.elapsed().as_secs_f64() * 1000.0);
+        let (outcome, status, detail, value) = match result {
+            Ok(value) => (Outcome::Pass, None, None, Some(value)),
+            Err(e) => (Outcome::Fail, e.status(), Some(e.to_string()), None),
+        };
+        self.record(StepResult { name: name.to_string(), outcome, latency_ms, status, detail });
+        value
+    }
+
+    pub fn skip(&mut self, name: &str, reason: impl Into<String>) {
+        self.record(StepResult {
+            name: name.to_string(),
+            outcome: Outcome::Skip,
+            latency_ms: None,
+            status: None,
+            detail: Some(reason.into()),
+        });
+    }
+
+    fn record(&mut self, step: StepResult) {
+        let latency = step.latency_ms.map(|ms| format!(" {ms:.1}ms")).unwrap_or_default();
+        eprintln!("  {:?} {}{latency}", step.outcome, step.name);
+        self.steps.push(step);
+    }
+
+    pub fn into_steps(self) -> Vec<StepResult> {
+        self.steps
+    }
+}
+
+/// Exercise the control plane end to end in a fresh organization: create
+/// and log in, then create, read and delete an endpoint and an interlay in
+/// front of it, and finally delete the organization. While the interlay is
+/// up, `PING`s through it are timed against direct ones, and the latency
+/// budgets in `config.slos` are checked once every step has run. With
+/// `config.soak`, traffic keeps flowing through the interlay for the soak
+/// duration before cleanup.
+pub async fn run(config: &SmokeConfig) -> Result<SmokeReport, ClientError> {
+    let redis = redis_target(&config.redis)?;
+    let started = Instant::now();
+    let mut client = EdenApiClient::new_with(&config.url, &config.tls, &config.transport)?;
+    let mut runner = Runner::default();
+
+    let mut admin = NewUser::new(&config.admin_username, &config.admin_password);
+    admin.perms = Some("RCPGDA".to_string());
+    let org = NewOrganization {
+        id: config.org.clone(),
+        description: Some("deploy-smoke run".to_string()),
+        super_admins: vec![admin],
+    };
+    runner.step("org.create", &[], client.create_organization(&org, config.new_org_token.as_deref())).await;
+    runner
+        .step(
+            "auth.login",
+            &["org.create"],
+            client.login(&config.org, &config.admin_username, &config.admin_password),
+        )
+        .await;
+    runner.step("org.get", &["auth.login"], client.get_organization()).await;
+
+    let endpoint_id = "smoke-cache";
+    let endpoint = NewEndpoint {
+        endpoint: endpoint_id.to_string(),
+        kind: "redis".to_string(),
+        config: json!({ "write_conn": { "host": redis.0, "port": redis.1, "tls": false } }),
+        description: Some("deploy-smoke endpoint".to_string()),
+    };
+    runner.step("endpoint.create", &["auth.login"], client.create_endpoint(&endpoint)).await;
+    runner.step("endpoint.list", &["auth.login"], client.list_endpoints()).await;
+    runner.step("endpoint.get", &["endpoint.create"], client.get_endpoint(endpoint_id)).await;
+
+    let interlay_id = "smoke-relay";
+    let interlay = NewInterlay {
+        id: interlay_id.to_string(),
+        endpoint: endpoint_id.to_string(),
+        port: None,
+        description: Some("deploy-smoke interlay".to_string()),
+        tls: None,
+        settings: json!({}),
+    };
+    let created = runner.step("interlay.create", &["endpoint.create"], client.create_interlay(&interlay)).await;
+    runner.step("interlay.list", &["auth.login"], client.list_interlays()).await;
+    runner.step("interlay.get", &["interlay.create"], client.get_interlay(interlay_id)).await;
+    runner.step("interlay.stop", &["interlay.create"], client.stop_interlay(interlay_id)).await;
+    runner.step("interlay.start", &["interlay.stop"], client.start_interlay(interlay_id)).await;
+
+    let interlay_addr = || {
+        let port = created.as_ref().and_then(|created| created.get("port")).and_then(Value::as_u64);
+        let port = port.ok_or_else(|| ClientError::Config("interlay create response has no port".to_string()))?;
+        Ok::<_, ClientError>(format!("{}:{port}", interlay_host(config)?))
+    };
+    let mut proxy_overhead = None;
+    if config.proxy_samples == 0 {
+        runner.skip("proxy.ping", "--proxy-samples 0");
+    } else {
+        let probe = async {
+            let proxied_addr = interlay_addr()?;
+            let direct = latency::ping_samples(&config.redis, config.proxy_samples).await?;
+            let proxied = latency::ping_samples(&proxied_addr, config.proxy_samples).await?;
+            Ok::<_, ClientError>((direct, proxied))
+        };
+        if let Some((direct, proxied)) = runner.step("proxy.ping", &["interlay.start"], probe).await
+            && let (Some(direct), Some(proxied)) = (Percentiles::of(&direct), Percentiles::of(&proxied))
+        {
+            proxy_overhead = Some(proxied.minus(&direct));
+        }
+    }
+
+    let mut soak_report = None;
+    if let Some(soak) = &config.soak {
+        let soak_run = async { soak::run(config, soak, &mut client, &interlay_addr()?).await };
+        soak_report = runner.step("soak", &["interlay.start"], soak_run).await;
+    }
+
+    for name in UNSUPPORTED_STEPS {
+        runner.skip(name, "service has no migration API");
+    }
+
+    runner.step("interlay.delete", &["interlay.create"], client.delete_interlay(interlay_id)).await;
+    runner.step("endpoint.delete", &["endpoint.create"], client.delete_endpoint(endpoint_id)).await;
+    if config.keep {
+        runner.skip("org.delete", "--keep");
+    } else {
+        runner.step("org.delete", &["auth.login"], client.delete_organization()).await;
+    }
+
+    let steps = runner.into_steps();
+    let api_samples: Vec<f64> = steps
+        .iter()
+        .filter(|step| step.outcome == Outcome::Pass && !TRAFFIC_STEPS.contains(&step.name.as_str()))
+        .filter_map(|step| step.latency_ms)
+        .collect();
+    let api_latency = Percentiles::of(&api_samples);
+    let slos = slo::evaluate(&config.slos, api_latency.as_ref(), proxy_overhead.as_ref());
+    Ok(SmokeReport {
+        url: config.url.clone(),
+        org: config.org.clone(),
+        passed: steps.iter().all(|step| step.outcome != Outcome::Fail)
+            && slos.iter().all(|slo| slo.passed)
+            && soak_report.as_ref().is_none_or(|soak| soak.passed()),
+        steps,
+        api_latency,
+        proxy_overhead,
+        slos,
+        soak: soak_report,
+        elapsed_secs: started.elapsed().as_secs_f64(),
+    })
+}
+
+fn interlay_host(config: &SmokeConfig) -> Result<String, ClientError> {
+    if let Some(host) = &config.interlay_host {
+        return Ok(host.clone());
+    }
+    let url = Url::parse(&config.url).map_err(|e| ClientError::Config(format!("invalid --url: {e}")))?;
+    url.host_str()
+        .map(str::to_string)
+        .ok_or_else(|| ClientError::Config(format!("--url `{}` has no host; pass --interlay-host", config.url)))
+}
+
+fn redis_target(addr: &str) -> Result<(&str, u16), ClientError> {
+    addr.rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse().ok()?)))
+        .ok_or_else(|| ClientError::Config(format!("--redis must be host:port, got `{addr}`")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_prerequisites_skip_dependent_steps() {
+        let mut runner = Runner::default();
+        runner.step("a", &[], async { Ok(()) }).await;
+        runner.step("b", &["a"], async { Err::<(), _>(ClientError::Unauthenticated) }).await;
+        let mut polled = false;
+        runner
+            .step("c", &["a", "b"], async {
+                polled = true;
+                Ok(())
+            })
+            .await;
+        assert!(!polled);
+
+        let steps = runner.into_steps();
+        let outcomes: Vec<Outcome> = steps.iter().map(|step| step.outcome).collect();
+        assert_eq!(outcomes, [Outcome::Pass, Outcome::Fail, Outcome::Skip]);
+        assert_eq!(steps[2].detail.as_deref(), Some("needs b"));
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::time::{Duration, Instant};
+
+use eden_client::{ClientError, EdenApiClient};
+use redis::aio::MultiplexedConnection;
+use redis::{RedisError, RedisResult, Value as RedisValue};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::smoke::SmokeConfig;
+
+/// Keys the churn cycles through, so the dataset stays bounded however long
+/// the soak runs.
+const CHURN_KEYS: u64 = 10_000;
+const CHURN_TICK: Duration = Duration::from_millis(100);
+/// Churned keys expire on their own if cleanup never runs.
+const CHURN_TTL_SECS: u64 = 600;
+const CHURN_VALUE: &str = "deploy-smoke-soak-0123456789abcdef0123456789abcdef0123456789abcdef";
+/// Fewer samples than this cannot show a trend.
+pub const MIN_LEAK_SAMPLES: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct SoakConfig {
+    pub duration: Duration,
+    pub sample_interval: Duration,
+    /// Commands per second sent through the interlay.
+    pub churn_rate: u32,
+    /// Second Redis instance to sample, e.g. a migration target.
+    pub target_redis: Option<String>,
+    /// A series that never decreased is flagged once it grew by more than
+    /// this percentage of its first sample.
+    pub leak_tolerance_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SoakSample {
+    pub elapsed_secs: f64,
+    /// e.g. `redis.used_memory` or `eden.proxy_connections`. A metric that
+    /// could not be read is left out of that sample.
+    pub metrics: BTreeMap<String, i64>,
+}
+
+/// A metric that grew monotonically over the soak.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Leak {
+    pub metric: String,
+    pub first: i64,
+    pub last: i64,
+    pub growth_pct: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SoakReport {
+    pub elapsed_secs: f64,
+    pub operations: u64,
+    pub failed_operations: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub samples: Vec<SoakSample>,
+    pub leaks: Vec<Leak>,
+}
+
+impl SoakReport {
+    /// A soak that ended before [`MIN_LEAK_SAMPLES`] samples cannot rule out
+    /// leaks, so it fails rather than passing unchecked.
+    pub fn too_few_samples(&self) -> bool {
+        self.samples.len() < MIN_LEAK_SAMPLES
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failed_operations == 0 && self.leaks.is_empty() && !self.too_few_samples()
+    }
+}
+
+/// Churn keys through the interlay at `interlay_addr` for `soak.duration`,
+/// sampling memory and connection counts on the Redis instances and the Eden
+/// API every `soak.sample_interval`. Sampling starts once a first pass has
+/// filled the keyspace, so the baseline is not an empty instance. Ctrl-C ends
+/// the soak early; the samples taken so far are still checked for leaks.
+pub async fn run(
+    config: &SmokeConfig,
+    soak: &SoakConfig,
+    client: &mut EdenApiClient,
+    interlay_addr: &str,
+) -> Result<SoakReport, ClientError> {
+    let mut churn = connect(interlay_addr).await?;
+    let mut instances = vec![("redis", connect(&config.redis).await?)];
+    if let Some(target) = &soak.target_redis {
+        instances.push(("target", connect(target).await?));
+    }
+
+    let per_tick = (u64::from(soak.churn_rate) * CHURN_TICK.as_millis() as u64 / 1000).max(1);
+    let mut ticker = tokio::time::interval(CHURN_TICK);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let interrupted = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    tokio::pin!(interrupted);
+
+    let started = Instant::now();
+    let deadline = started + soak.duration;
+    // Unset until the warm-up pass over CHURN_KEYS is done.
+    let mut next_sample = None;
+    let mut report = SoakReport::default();
+    let mut next_key = 0;
+    while Instant::now() < deadline {
+        if next_sample.is_none() && next_key >= CHURN_KEYS {
+            eprintln!("  soak: keyspace warmed up after {:.0}s", started.elapsed().as_secs_f64());
+            next_sample = Some(Instant::now());
+        }
+        if let Some(due) = next_sample
+            && Instant::now() >= due
+        {
+            let sample = take_sample(config, client, &mut instances, started).await;
+            report.samples.push(sample);
+            next_sample = Some(due + soak.sample_interval);
+        }
+        tokio::select! {
+            _ = &mut interrupted => {
+                eprintln!("  soak: interrupted");
+                break;
+            }
+            _ = ticker.tick() => {}
+        }
+        report.operations += per_tick;
+        if let Err(e) = churn_batch(&mut churn, &mut next_key, per_tick).await {
+            report.failed_operations += per_tick;
+            report.last_error = Some(e.to_string());
+        }
+    }
+
+    let sample = take_sample(config, client, &mut instances, started).await;
+    report.samples.push(sample);
+    report.elapsed_secs = started.elapsed().as_secs_f64();
+    report.leaks = detect_leaks(&report.samples, soak.leak_tolerance_pct);
+    Ok(report)
+}
+
+async fn connect(addr: &str) -> Result<MultiplexedConnection, ClientError> {
+    open(addr).await.map_err(|e| ClientError::Io(io::Error::other(format!("redis {addr}: {e}"))))
+}
+
+async fn open(addr: &str) -> RedisResult<MultiplexedConnection> {
+    redis::Client::open(format!("redis://{addr}"))?.get_multiplexed_async_connection().await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChurnOp {
+    Set,
+    Get,
+    Del,
+}
+
+/// Key slot and command for the `n`th churned operation: mostly SETs and
+/// GETs, with a DEL one time in ten. The command shifts with every pass over
+/// the keys, so each key is in turn written, read and deleted and the key
+/// count churns as well as the values.
+fn churn_op(n: u64) -> (u64, ChurnOp) {
+    let op = match (n / CHURN_KEYS + n) % 10 {
+        9 => ChurnOp::Del,
+        slot if slot % 2 == 0 => ChurnOp::Set,
+        _ => ChurnOp::Get,
+    };
+    (n % CHURN_KEYS, op)
+}
+
+/// One pipeline of `count` churn operations.
+async fn churn_batch(connection: &mut MultiplexedConnection, next_key: &mut u64, count: u64) -> Result<(), RedisError> {
+    let mut pipe = redis::pipe();
+    for _ in 0..count {
+        let (slot, op) = churn_op(*next_key);
+        *next_key += 1;
+        let key = format!("deploy-smoke:soak:{slot}");
+        match op {
+            ChurnOp::Set => pipe.set_ex(key, CHURN_VALUE, CHURN_TTL_SECS),
+            ChurnOp::Get => pipe.get(key),
+            ChurnOp::Del => pipe.del(key),
+        };
+    }
+    pipe.query_async::<Vec<RedisValue>>(connection).await.map(drop)
+}
+
+async fn take_sample(
+    config: &SmokeConfig,
+    client: &mut EdenApiClient,
+    instances: &mut [(&str, MultiplexedConnection)],
+    started: Instant,
+) -> SoakSample {
+    let mut metrics = BTreeMap::new();
+    for (name, connection) in instances.iter_mut() {
+        for section in ["memory", "clients"] {
+            match redis::cmd("INFO").arg(section).query_async::<String>(connection).await {
+                Ok(info) => metrics.extend(parse_info(name, &info)),
+                Err(e) => eprintln!("  soak: INFO {section} on {name} failed: {e}"),
+            }
+        }
+    }
+    match eden_connections(config, client).await {
+        Ok(current) => {
+            for (metric, field) in [
+                ("eden.endpoint_connections", "endpoint_connections_total"),
+                ("eden.proxy_connections", "endpoint_proxy_connections_total"),
+            ] {
+                if let Some(value) = current.get(field).and_then(Value::as_i64) {
+                    metrics.insert(metric.to_string(), value);
+                }
+            }
+        }
+        Err(e) => eprintln!("  soak: connection metrics failed: {e}"),
+    }
+
+    let elapsed_secs = started.elapsed().as_secs_f64();
+    let summary: Vec<String> = metrics.iter().map(|(metric, value)| format!("{metric}={value}")).collect();
+    eprintln!("  soak: t={elapsed_secs:.0}s {}", summary.join(" "));
+    SoakSample { elapsed_secs, metrics }
+}
+
+/// The `current` connection counts, logging in again when the token expired
+/// during a long soak.
+async fn eden_connections(config: &SmokeConfig, client: &mut EdenApiClient) -> Result<Value, ClientError> {
+    let reply = match client.connection_metrics().await {
+        Err(e) if e.status() == Some(401) => {
+            client.login(&config.org, &config.admin_username, &config.admin_password).await?;
+            client.connection_metrics().await?
+        }
+        reply => reply?,
+    };
+    Ok(reply.get("current").cloned().unwrap_or_default())
+}
+
+/// `used_memory` and `connected_clients` from an `INFO` reply, prefixed with
+/// the instance name.
+fn parse_info(instance: &str, info: &str) -> Vec<(String, i64)> {
+    info.lines()
+        .filter_map(|line| line.trim_end().split_once(':'))
+        .filter(|(field, _)| matches!(*field, "used_memory" | "connected_clients"))
+        .filter_map(|(field, value)| Some((format!("{instance}.{field}"), value.parse().ok()?)))
+        .collect()
+}
+
+/// Flag every metric that never decreased across at least
+/// [`MIN_LEAK_SAMPLES`] samples and grew by more than `tolerance_pct`.
+pub fn detect_leaks(samples: &[SoakSample], tolerance_pct: f64) -> Vec<Leak> {
+    let mut series: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+    for sample in samples {
+        for (metric, value) in &sample.metrics {
+            series.entry(metric).or_default().push(*value);
+        }
+    }
+    series
+        .into_iter()
+        .filter(|(_, values)| values.len() >= MIN_LEAK_SAMPLES && values.windows(2).all(|pair| pair[1] >= pair[0]))
+        .filter_map(|(metric, values)| {
+            let (first, last) = (values[0], values[values.len() - 1]);
+            let growth_pct = (last - first) as f64 / first.max(1) as f64 * 100.0;
+            (growth_pct > tolerance_pct).then(|| Leak { metric: metric.to_string(), first, last, growth_pct })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(metrics: &[(&str, i64)]) -> SoakSample {
+        SoakSample {
+            elapsed_secs: 0.0,
+            metrics: metrics.iter().map(|(metric, value)| (metric.to_string(), *value)).collect(),
+        }
+    }
+
+    #[test]
+    fn only_monotonic_growth_past_the_tolerance_is_a_leak() {
+        let samples = [
+            sample(&[
+                ("redis.used_memory", 1000),
+                ("redis.connected_clients", 4),
+                ("eden.proxy_connections", 2),
+            ]),
+            sample(&[
+                ("redis.used_memory", 1100),
+                ("redis.connected_clients", 9),
+                ("eden.proxy_connections", 2),
+            ]),
+            sample(&[
+                ("redis.used_memory", 1300),
+                ("redis.connected_clients", 5),
+                ("eden.proxy_connections", 2),
+            ]),
+        ];
+        let leaks = detect_leaks(&samples, 10.0);
+        assert_eq!(
+            leaks,
+            [Leak {
+                metric: "redis.used_memory".to_string(),
+                first: 1000,
+                last: 1300,
+                growth_pct: 30.0
+            }]
+        );
+        assert!(detect_leaks(&samples, 50.0).is_empty());
+        assert!(detect_leaks(&samples[..2], 10.0).is_empty());
+    }
+
+    #[test]
+    fn a_soak_too_short_to_check_for_leaks_fails() {
+        let mut report = SoakReport {
+            samples: vec![sample(&[("redis.used_memory", 1000)]); MIN_LEAK_SAMPLES - 1],
+            ..SoakReport::default()
+        };
+        assert!(report.too_few_samples() && !report.passed());
+        report.samples.push(sample(&[("redis.used_memory", 1000)]));
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn every_key_is_set_read_and_deleted_across_passes() {
+        let ops: Vec<ChurnOp> = (0..10)
+            .map(|pass| churn_op(pass * CHURN_KEYS + 7))
+            .inspect(|(slot, _)| assert_eq!(*slot, 7))
+            .map(|(_, op)| op)
+            .collect();
+        for op in [ChurnOp::Set, ChurnOp::Get, ChurnOp::Del] {
+            assert!(ops.contains(&op), "{op:?} missing from {ops:?}");
+        }
+    }
+
+    #[test]
+    fn info_fields_are_prefixed_with_the_instance() {
+        let info = "# Memory\r\nused_memory:1048576\r\nused_memory_human:1.00M\r\n# Clients\r\nconnected_clients:3\r\n";
+        assert_eq!(
+            parse_info("target", info),
+            [
+                ("target.used_memory".to_string(), 1_048_576),
+                ("target.connected_clients".to_string(), 3)
+            ]
+        );
+    }
+}
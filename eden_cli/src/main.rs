@@ -0,0 +1,433 @@
+mod output;
+
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+
+use clap::{Parser, Subcommand};
+use serde_json::Value;
+
+use eden_client::audit::{AuditSink, JsonlAudit};
+use eden_client::profile::{self, ProfileConfig};
+use eden_client::tls::TlsConfig;
+use eden_client::token::{CachedToken, TokenCache};
+use eden_client::transport::TransportConfig;
+use eden_client::types::{
+    MongoEndpointConfig, MysqlEndpointConfig, NewEndpoint, NewInterlay, NewOrganization, NewUser, PostgresEndpointConfig,
+};
+use eden_client::{ClientError, EdenApiClient};
+
+use output::OutputFormat;
+
+const ENDPOINT_COLUMNS: &[&str] = &["id", "uuid", "kind", "description"];
+const INTERLAY_COLUMNS: &[&str] = &["id", "uuid", "endpoint", "port", "running"];
+const USER_COLUMNS: &[&str] = &["username", "uuid", "email", "display_name"];
+
+/// Script the Eden control plane.
+#[derive(Parser)]
+#[command(name = "eden")]
+struct Cli {
+    /// Profile from ~/.eden/config.toml.
+    #[arg(long, global = true, env = "EDEN_PROFILE")]
+    profile: Option<String>,
+
+    /// Eden service root, e.g. http://localhost:8000. Overrides the profile.
+    #[arg(long, global = true, env = "EDEN_URL")]
+    url: Option<String>,
+
+    /// Organization ID. Overrides the profile.
+    #[arg(long, global = true, env = "EDEN_ORG")]
+    org: Option<String>,
+
+    /// API token to use instead of the cached login.
+    #[arg(long, global = true, env = "EDEN_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    #[arg(long, global = true, value_enum, default_value = "table")]
+    output: OutputFormat,
+
+    /// Append every API call, with secrets redacted, to this JSONL file.
+    #[arg(long, global = true, env = "EDEN_AUDIT_LOG")]
+    audit_log: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Log in and cache the token for the selected profile.
+    Login {
+        /// Defaults to the profile's username.
+        #[arg(long, env = "EDEN_USERNAME")]
+        username: Option<String>,
+        /// Defaults to the profile's password.
+        #[arg(long, env = "EDEN_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Forget the cached token for the selected profile.
+    Logout,
+    /// Create and inspect organizations.
+    #[command(subcommand)]
+    Org(OrgCommand),
+    /// Manage database endpoints.
+    #[command(subcommand)]
+    Endpoint(EndpointCommand),
+    /// Manage interlays, the proxies placed in front of endpoints.
+    #[command(subcommand)]
+    Interlay(InterlayCommand),
+    /// Manage users and their organization roles.
+    #[command(subcommand)]
+    User(UserCommand),
+}
+
+#[derive(Subcommand)]
+enum OrgCommand {
+    /// Create an organization with its first super admin.
+    Create {
+        /// Organization ID; defaults to --org.
+        #[arg(long)]
+        id: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        #[arg(long)]
+        admin_username: String,
+        #[arg(long, env = "EDEN_ADMIN_PASSWORD", hide_env_values = true)]
+        admin_password: String,
+        /// Bearer token required by deployments that set EDEN_NEW_ORG_TOKEN.
+        /// Defaults to the profile's token.
+        #[arg(long, env = "EDEN_NEW_ORG_TOKEN", hide_env_values = true)]
+        new_org_token: Option<String>,
+    },
+    /// Show the organization of the logged-in user.
+    Get,
+}
+
+#[derive(Subcommand)]
+enum EndpointCommand {
+    List,
+    Get {
+        endpoint: String,
+    },
+    /// Register a database endpoint.
+    Add {
+        /// Endpoint ID.
+        #[arg(long)]
+        name: String,
+        /// Endpoint kind, e.g. redis, postgres, mysql, mongo.
+        #[arg(long)]
+        kind: String,
+        /// Kind-specific connection config as JSON, or @file to read it from a file.
+        #[arg(long, required_unless_present = "connection_url", conflicts_with = "connection_url")]
+        config: Option<String>,
+        /// Connection URL, for kinds that accept one instead of --config (postgres, mysql, mongo).
+        #[arg(long)]
+        connection_url: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+    },
+    Delete {
+        endpoint: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum InterlayCommand {
+    List,
+    Get {
+        interlay: String,
+    },
+    /// Create an interlay in front of an endpoint. New interlays start running.
+    Create {
+        #[arg(long)]
+        id: String,
+        /// Endpoint ID or UUID to route to.
+        #[arg(long)]
+        endpoint: String,
+        /// Listener port; the service picks one when omitted.
+        #[arg(long)]
+        port: Option<u16>,
+        #[arg(long)]
+        description: Option<String>,
+        /// Interlay settings as JSON, or @file to read them from a file.
+        #[arg(long, default_value = "{}")]
+        settings: String,
+    },
+    Start {
+        interlay: String,
+    },
+    Stop {
+        interlay: String,
+    },
+    Delete {
+        interlay: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum UserCommand {
+    /// List the first page of organization members.
+    List,
+    Get {
+        user: String,
+    },
+    /// Create a user. Without --perms the service grants read-only access.
+    Create {
+        #[arg(long)]
+        username: String,
+        #[arg(long, env = "EDEN_USER_PASSWORD", hide_env_values = true)]
+        password: String,
+        #[arg(long)]
+        email: Option<String>,
+        #[arg(long)]
+        display_name: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// Control-plane permissions, e.g. RG or RCPGDA.
+        #[arg(long)]
+        perms: Option<String>,
+    },
+    /// Set a new password for a user.
+    Passwd {
+        user: String,
+        #[arg(long, env = "EDEN_USER_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+    Delete {
+        user: String,
+    },
+    /// Inspect and change organization-level roles.
+    #[command(subcommand)]
+    Role(RoleCommand),
+}
+
+#[derive(Subcommand)]
+enum RoleCommand {
+    Get {
+        /// User or group ID or UUID.
+        subject: String,
+    },
+    /// Replace the subject's organization permissions.
+    Set {
+        subject: String,
+        /// Control-plane permissions, e.g. RG or RCPGDA.
+        #[arg(long)]
+        perms: String,
+    },
+    /// Revoke the subject's organization permissions.
+    Remove { subject: String },
+}
+
+/// Connection settings after merging flags, environment and profile.
+struct Session {
+    profile: String,
+    url: String,
+    org: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    new_org_token: Option<String>,
+    tls: TlsConfig,
+    transport: TransportConfig,
+    token: Option<String>,
+    cache: TokenCache,
+    audit: Option<Arc<dyn AuditSink>>,
+}
+
+impl Session {
+    fn resolve(cli: &Cli) -> Result<Self, ClientError> {
+        let dir = profile::config_dir()?;
+        let (name, profile) = ProfileConfig::load(&dir)?.resolve(cli.profile.as_deref())?;
+        let url = cli
+            .url
+            .clone()
+            .or(profile.url)
+            .ok_or_else(|| ClientError::Config(format!("no API URL; pass --url or set `url` in profile `{name}`")))?;
+        Ok(Self {
+            profile: name,
+            url,
+            org: cli.org.clone().or(profile.org),
+            username: profile.username,
+            password: profile.password,
+            new_org_token: profile.new_org_token,
+            tls: profile.tls,
+            transport: profile.transport,
+            token: cli.token.clone(),
+            cache: TokenCache::load(&dir)?,
+            audit: match &cli.audit_log {
+                Some(path) => Some(Arc::new(JsonlAudit::open(path)?)),
+                None => None,
+            },
+        })
+    }
+
+    /// An unauthenticated client for this session's service.
+    fn client(&self) -> Result<EdenApiClient, ClientError> {
+        let client = EdenApiClient::new_with(&self.url, &self.tls, &self.transport)?;
+        Ok(match &self.audit {
+            Some(audit) => client.with_audit(Arc::clone(audit)),
+            None => client,
+        })
+    }
+
+    fn org(&self) -> Result<&str, ClientError> {
+        self.org
+            .as_deref()
+            .ok_or_else(|| ClientError::Config(format!("no organization; pass --org or set `org` in profile `{}`", self.profile)))
+    }
+
+    /// A client carrying the explicit or cached token for this profile.
+    fn authenticated(&self) -> Result<EdenApiClient, ClientError> {
+        let client = self.client()?;
+        if let Some(token) = &self.token {
+            return Ok(client.with_token(token));
+        }
+        let token = self.cache.get(&self.profile, &self.url, self.org()?).ok_or_else(|| {
+            ClientError::Config(format!("not logged in to profile `{}` (or the token expired); run `eden login`", self.profile))
+        })?;
+        Ok(client.with_token(token))
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("error: {e}");
+        process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), ClientError> {
+    let mut session = Session::resolve(&cli)?;
+    let format = cli.output;
+
+    match cli.command {
+        Command::Login { username, password } => {
+            let org = session.org()?.to_string();
+            let username = username
+                .or(session.username.clone())
+                .ok_or_else(|| ClientError::Config("no username; pass --username or set `username` in the profile".to_string()))?;
+            let password = password
+                .or(session.password.clone())
+                .ok_or_else(|| ClientError::Config("no password; pass --password or set `password` in the profile".to_string()))?;
+            let mut client = session.client()?;
+            let token = client.login(&org, &username, &password).await?;
+            session.cache.insert(&session.profile, CachedToken { url: session.url.clone(), org, token });
+            session.cache.save()?;
+            eprintln!("logged in to {} as {username} (profile `{}`)", session.url, session.profile);
+        }
+        Command::Logout => {
+            if session.cache.remove(&session.profile) {
+                session.cache.save()?;
+            }
+            eprintln!("logged out of profile `{}`", session.profile);
+        }
+        Command::Org(OrgCommand::Create {
+            id,
+            description,
+            admin_username,
+            admin_password,
+            new_org_token,
+        }) => {
+            let id = match id {
+                Some(id) => id,
+                None => session.org()?.to_string(),
+            };
+            let mut admin = NewUser::new(admin_username, admin_password);
+            admin.perms = Some("RCPGDA".to_string());
+            let org = NewOrganization { id, description, super_admins: vec![admin] };
+            let client = session.client()?;
+            let new_org_token = new_org_token.or(session.new_org_token.clone());
+            output::print(&client.create_organization(&org, new_org_token.as_deref()).await?, format, &[]);
+        }
+        Command::Org(OrgCommand::Get) => output::print(&session.authenticated()?.get_organization().await?, format, &[]),
+        Command::Endpoint(command) => {
+            let client = session.authenticated()?;
+            let (value, columns) = match command {
+                EndpointCommand::List => (client.list_endpoints().await?, ENDPOINT_COLUMNS),
+                EndpointCommand::Get { endpoint } => (client.get_endpoint(&endpoint).await?, &[][..]),
+                EndpointCommand::Add { name, kind, config, connection_url, description } => {
+                    let mut endpoint = match connection_url {
+                        Some(url) => endpoint_from_url(name, &kind, url)?,
+                        None => {
+                            let config = json_arg(config.as_deref().unwrap_or_default())?;
+                            NewEndpoint { endpoint: name, kind, config, description: None }
+                        }
+                    };
+                    endpoint.description = description;
+                    (client.create_endpoint(&endpoint).await?, &[][..])
+                }
+                EndpointCommand::Delete { endpoint } => (client.delete_endpoint(&endpoint).await?, &[][..]),
+            };
+            output::print(&value, format, columns);
+        }
+        Command::Interlay(command) => {
+            let client = session.authenticated()?;
+            let (value, columns) = match command {
+                InterlayCommand::List => (client.list_interlays().await?, INTERLAY_COLUMNS),
+                InterlayCommand::Get { interlay } => (client.get_interlay(&interlay).await?, &[][..]),
+                InterlayCommand::Create { id, endpoint, port, description, settings } => {
+                    let interlay = NewInterlay {
+                        id,
+                        endpoint,
+                        port,
+                        description,
+                        tls: None,
+                        settings: json_arg(&settings)?,
+                    };
+                    (client.create_interlay(&interlay).await?, &[][..])
+                }
+                InterlayCommand::Start { interlay } => (client.start_interlay(&interlay).await?, &[][..]),
+                InterlayCommand::Stop { interlay } => (client.stop_interlay(&interlay).await?, &[][..]),
+                InterlayCommand::Delete { interlay } => (client.delete_interlay(&interlay).await?, &[][..]),
+            };
+            output::print(&value, format, columns);
+        }
+        Command::User(command) => {
+            let client = session.authenticated()?;
+            let (value, columns) = match command {
+                UserCommand::List => (client.list_users().await?, USER_COLUMNS),
+                UserCommand::Get { user } => (client.get_user(&user).await?, &[][..]),
+                UserCommand::Create { username, password, email, display_name, description, perms } => {
+                    let user = NewUser {
+                        email,
+                        display_name,
+                        description,
+                        perms,
+                        ..NewUser::new(username, password)
+                    };
+                    (client.create_user(&user).await?, &[][..])
+                }
+                UserCommand::Passwd { user, password } => (client.set_user_password(&user, &password).await?, &[][..]),
+                UserCommand::Delete { user } => (client.delete_user(&user).await?, &[][..]),
+                UserCommand::Role(RoleCommand::Get { subject }) => (client.get_org_role(&subject).await?, &[][..]),
+                UserCommand::Role(RoleCommand::Set { subject, perms }) => (client.set_org_role(&subject, &perms).await?, &[][..]),
+                UserCommand::Role(RoleCommand::Remove { subject }) => (client.remove_org_role(&subject).await?, &[][..]),
+            };
+            output::print(&value, format, columns);
+        }
+    }
+    Ok(())
+}
+
+/// Build an endpoint from a connection URL, for kinds with a URL shorthand.
+fn endpoint_from_url(name: String, kind: &str, url: String) -> Result<NewEndpoint, ClientError> {
+    match kind {
+        "postgres" => Ok(NewEndpoint::postgres(name, &PostgresEndpointConfig::new(url))),
+        "mysql" => Ok(NewEndpoint::mysql(name, &MysqlEndpointConfig::from_url(&url)?)),
+        "mongo" => Ok(NewEndpoint::mongo(name, &MongoEndpointConfig::new(url))),
+        other => Err(ClientError::Config(format!(
+            "--connection-url is not supported for {other} endpoints; use --config"
+        ))),
+    }
+}
+
+/// Parse a JSON argument given inline or as `@path`.
+fn json_arg(arg: &str) -> Result<Value, ClientError> {
+    let text = match arg.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path)?,
+        None => arg.to_string(),
+    };
+    serde_json::from_str(&text).map_err(|e| ClientError::Config(format!("invalid JSON argument: {e}")))
+}
@@ -0,0 +1,125 @@
+use clap::ValueEnum;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Pretty-printed JSON as returned by the API.
+    Json,
+    /// Aligned columns for reading in a terminal.
+    Table,
+}
+
+/// Print an API response to stdout in the requested format. `columns` picks
+/// the fields shown when the response is a list; an empty slice shows every
+/// scalar field of the first row.
+pub fn print(value: &Value, format: OutputFormat, columns: &[&str]) {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value).expect("JSON serialization")),
+        OutputFormat::Table => print!("{}", render_table(value, columns)),
+    }
+}
+
+pub fn render_table(value: &Value, columns: &[&str]) -> String {
+    match list_rows(value) {
+        Some(rows) => render_rows(rows, columns),
+        None => match value {
+            Value::Object(map) => {
+                let rows: Vec<Vec<String>> = map.iter().map(|(key, value)| vec![key.clone(), cell(value)]).collect();
+                layout(&["FIELD".to_string(), "VALUE".to_string()], &rows)
+            }
+            Value::Null => String::new(),
+            other => format!("{}\n", cell(other)),
+        },
+    }
+}
+
+/// List responses are either bare arrays or objects wrapping a single array,
+/// such as `{"endpoints": [...]}`, optionally with a pagination `next_cursor`.
+fn list_rows(value: &Value) -> Option<&[Value]> {
+    match value {
+        Value::Array(rows) => Some(rows),
+        Value::Object(map) => {
+            let mut fields = map.iter().filter(|(key, _)| key.as_str() != "next_cursor");
+            match (fields.next(), fields.next()) {
+                (Some((_, rows)), None) => rows.as_array().map(Vec::as_slice),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn render_rows(rows: &[Value], columns: &[&str]) -> String {
+    let columns: Vec<String> = if columns.is_empty() {
+        match rows.first() {
+            Some(Value::Object(first)) => {
+                first.iter().filter(|(_, value)| !value.is_object() && !value.is_array()).map(|(key, _)| key.clone()).collect()
+            }
+            _ => vec!["VALUE".to_string()],
+        }
+    } else {
+        columns.iter().map(|column| column.to_string()).collect()
+    };
+
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| match row {
+            Value::Object(map) => columns.iter().map(|column| map.get(column).map(cell).unwrap_or_default()).collect(),
+            other => vec![cell(other)],
+        })
+        .collect();
+    let header: Vec<String> = columns.iter().map(|column| column.to_uppercase()).collect();
+    layout(&header, &cells)
+}
+
+fn layout(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(String::len).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.len());
+        }
+    }
+
+    let mut out = String::new();
+    for row in std::iter::once(header).chain(rows.iter().map(Vec::as_slice)) {
+        let line: Vec<String> = row.iter().zip(&widths).map(|(value, width)| format!("{value:<width$}")).collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn wrapped_lists_render_selected_columns() {
+        let value = json!({ "endpoints": [
+            { "id": "cache", "kind": "redis", "config": {} },
+            { "id": "orders-db", "kind": "postgres", "description": null },
+        ]});
+        assert_eq!(render_table(&value, &["id", "kind"]), "ID         KIND\ncache      redis\norders-db  postgres\n");
+    }
+
+    #[test]
+    fn paginated_lists_ignore_the_cursor() {
+        let value = json!({ "humans": [{ "username": "ci" }], "next_cursor": "1735689600000000000_abc" });
+        assert_eq!(render_table(&value, &["username"]), "USERNAME\nci\n");
+    }
+
+    #[test]
+    fn objects_render_as_field_value_pairs() {
+        let value = json!({ "id": "relay", "running": true });
+        assert_eq!(render_table(&value, &[]), "FIELD    VALUE\nid       relay\nrunning  true\n");
+    }
+}
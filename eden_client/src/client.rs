@@ -0,0 +1,308 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::audit::{self, AuditEntry, AuditSink};
+use crate::error::ClientError;
+use crate::tls::TlsConfig;
+use crate::transport::TransportConfig;
+use crate::types::{JwtResponse, NewEndpoint, NewInterlay, NewOrganization, NewUser, RoleGrant, UserUpdate};
+
+const API_PREFIX: &str = "/api/v1";
+/// Attempts made when the service keeps answering 429 Too Many Requests.
+const RATE_LIMIT_RETRIES: u32 = 10;
+const MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Client for the Eden `/api/v1` control plane.
+///
+/// Responses are returned as JSON values: the client only needs to move them
+/// between the service and its callers, and staying schema-agnostic keeps it
+/// working across service versions.
+#[derive(Debug, Clone)]
+pub struct EdenApiClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    audit: Option<Arc<dyn AuditSink>>,
+}
+
+impl EdenApiClient {
+    /// `base_url` is the service root, e.g. `http://localhost:8000`.
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        Self::new_with(base_url, &TlsConfig::default(), &TransportConfig::default())
+    }
+
+    /// Like [`EdenApiClient::new`], with custom TLS settings (a private CA,
+    /// a client certificate) and transport settings (a proxy, timeouts).
+    pub fn new_with(base_url: &str, tls: &TlsConfig, transport: &TransportConfig) -> Result<Self, ClientError> {
+        let http = tls.apply(transport.apply(reqwest::Client::builder())?)?.build()?;
+        Ok(Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: None,
+            audit: None,
+        })
+    }
+
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Report every call, including failed ones, to `sink`.
+    pub fn with_audit(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit = Some(sink);
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    /// Exchange username and password for a JWT and keep it for later calls.
+    pub async fn login(&mut self, org_id: &str, username: &str, password: &str) -> Result<String, ClientError> {
+        let request = self
+            .http
+            .post(self.url("/auth/login"))
+            .header("X-Org-Id", org_id)
+            .basic_auth(username, Some(password))
+            .json(&serde_json::json!({ "id": org_id }));
+        let reply: JwtResponse = parse(self.send(Method::POST, "/auth/login", request).await?)?;
+        self.token = Some(reply.token.clone());
+        Ok(reply.token)
+    }
+
+    /// Trade the current token for a fresh one.
+    pub async fn refresh(&mut self) -> Result<String, ClientError> {
+        let reply: JwtResponse = parse(self.request(Method::POST, "/auth/refresh", None::<&()>).await?)?;
+        self.token = Some(reply.token.clone());
+        Ok(reply.token)
+    }
+
+    /// Create an organization. Deployments that set `EDEN_NEW_ORG_TOKEN`
+    /// require that token as bearer credentials.
+    pub async fn create_organization(&self, org: &NewOrganization, new_org_token: Option<&str>) -> Result<Value, ClientError> {
+        let mut request = self.http.post(self.url("/new")).json(org);
+        if let Some(token) = new_org_token {
+            request = request.bearer_auth(token);
+        }
+        self.send(Method::POST, "/new", request).await
+    }
+
+    pub async fn get_organization(&self) -> Result<Value, ClientError> {
+        self.request(Method::GET, "/organizations", None::<&()>).await
+    }
+
+    /// Delete the organization of the logged-in user, with everything in it.
+    pub async fn delete_organization(&self) -> Result<Value, ClientError> {
+        self.request(Method::DELETE, "/organizations", None::<&()>).await
+    }
+
+    /// First page of users in the organization.
+    pub async fn list_users(&self) -> Result<Value, ClientError> {
+        self.request(Method::GET, "/iam/humans", None::<&()>).await
+    }
+
+    pub async fn get_user(&self, user: &str) -> Result<Value, ClientError> {
+        self.request(Method::GET, &format!("/iam/humans/{user}"), None::<&()>).await
+    }
+
+    pub async fn create_user(&self, user: &NewUser) -> Result<Value, ClientError> {
+        self.request(Method::POST, "/iam/humans", Some(user)).await
+    }
+
+    pub async fn update_user(&self, user: &str, update: &UserUpdate) -> Result<Value, ClientError> {
+        self.request(Method::PATCH, &format!("/iam/humans/{user}"), Some(update)).await
+    }
+
+    pub async fn set_user_password(&self, user: &str, password: &str) -> Result<Value, ClientError> {
+        let update = UserUpdate {
+            password: Some(password.to_string()),
+            ..UserUpdate::default()
+        };
+        self.update_user(user, &update).await
+    }
+
+    pub async fn delete_user(&self, user: &str) -> Result<Value, ClientError> {
+        self.request(Method::DELETE, &format!("/iam/humans/{user}"), None::<&()>).await
+    }
+
+    /// Organization-level control-plane grant held by a user or group.
+    pub async fn get_org_role(&self, subject: &str) -> Result<Value, ClientError> {
+        self.request(Method::GET, &format!("/iam/control/organizations/subjects/{subject}"), None::<&()>).await
+    }
+
+    /// Replace the organization-level grant of `subject` with `perms`.
+    pub async fn set_org_role(&self, subject: &str, perms: &str) -> Result<Value, ClientError> {
+        let grant = RoleGrant { perms: perms.to_string() };
+        self.request(Method::PUT, &format!("/iam/control/organizations/subjects/{subject}"), Some(&grant)).await
+    }
+
+    pub async fn remove_org_role(&self, subject: &str) -> Result<Value, ClientError> {
+        self.request(Method::DELETE, &format!("/iam/control/organizations/subjects/{subject}"), None::<&()>).await
+    }
+
+    pub async fn list_endpoints(&self) -> Result<Value, ClientError> {
+        self.request(Method::GET, "/endpoints", None::<&()>).await
+    }
+
+    pub async fn get_endpoint(&self, endpoint: &str) -> Result<Value, ClientError> {
+        self.request(Method::GET, &format!("/endpoints/{endpoint}"), None::<&()>).await
+    }
+
+    pub async fn create_endpoint(&self, endpoint: &NewEndpoint) -> Result<Value, ClientError> {
+        self.request(Method::POST, "/endpoints", Some(endpoint)).await
+    }
+
+    pub async fn delete_endpoint(&self, endpoint: &str) -> Result<Value, ClientError> {
+        self.request(Method::DELETE, &format!("/endpoints/{endpoint}"), None::<&()>).await
+    }
+
+    pub async fn list_interlays(&self) -> Result<Value, ClientError> {
+        self.request(Method::GET, "/interlays", None::<&()>).await
+    }
+
+    pub async fn get_interlay(&self, interlay: &str) -> Result<Value, ClientError> {
+        self.request(Method::GET, &format!("/interlays/{interlay}"), None::<&()>).await
+    }
+
+    pub async fn create_interlay(&self, interlay: &NewInterlay) -> Result<Value, ClientError> {
+        self.request(Method::POST, "/interlays", Some(interlay)).await
+    }
+
+    pub async fn delete_interlay(&self, interlay: &str) -> Result<Value, ClientError> {
+        self.request(Method::DELETE, &format!("/interlays/{interlay}"), None::<&()>).await
+    }
+
+    pub async fn start_interlay(&self, interlay: &str) -> Result<Value, ClientError> {
+        self.request(Method::POST, &format!("/interlays/{interlay}/start"), None::<&()>).await
+    }
+
+    pub async fn stop_interlay(&self, interlay: &str) -> Result<Value, ClientError> {
+        self.request(Method::POST, &format!("/interlays/{interlay}/stop"), None::<&()>).await
+    }
+
+    /// Live connection counts for the organization: endpoint pools, proxy
+    /// clients and in-flight API requests.
+    pub async fn connection_metrics(&self) -> Result<Value, ClientError> {
+        self.request(Method::GET, "/analytics/connections", None::<&()>).await
+    }
+
+    /// Send an authenticated request to any `/api/v1` path. An empty response
+    /// body is returned as `Value::Null`.
+    pub async fn request<B: Serialize + ?Sized>(&self, method: Method, path: &str, body: Option<&B>) -> Result<Value, ClientError> {
+        let token = self.token.as_deref().ok_or(ClientError::Unauthenticated)?;
+        let mut request = self.http.request(method.clone(), self.url(path)).bearer_auth(token);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        self.send(method, path, request).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{API_PREFIX}{path}", self.base_url)
+    }
+
+    /// Send `request` and report it to the audit sink, if any.
+    async fn send(&self, method: Method, path: &str, request: RequestBuilder) -> Result<Value, ClientError> {
+        let Some(sink) = &self.audit else {
+            return self.send_with_retries(&method, path, &request).await.result;
+        };
+        let built = request.try_clone().expect("request bodies are buffered JSON").build()?;
+        let body = built.body().and_then(|body| body.as_bytes()).and_then(|bytes| serde_json::from_slice(bytes).ok());
+
+        let started = Instant::now();
+        let outcome = self.send_with_retries(&method, path, &request).await;
+        sink.record(&AuditEntry {
+            timestamp_ms: audit::now_ms(),
+            method: method.to_string(),
+            url: built.url().to_string(),
+            request: body.as_ref().map(audit::redact),
+            status: outcome.status,
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            attempts: outcome.attempts,
+            response: outcome.result.as_ref().ok().map(audit::redact),
+            error: outcome.result.as_ref().err().map(ToString::to_string),
+        });
+        outcome.result
+    }
+
+    /// Send `request`, backing off while the service rate-limits us.
+    async fn send_with_retries(&self, method: &Method, path: &str, request: &RequestBuilder) -> Outcome {
+        let mut outcome = Outcome { result: Ok(Value::Null), status: None, attempts: 0 };
+        let mut backoff = Duration::from_millis(100);
+        loop {
+            outcome.attempts += 1;
+            let retry = request.try_clone().expect("request bodies are buffered JSON");
+            let response = match retry.send().await {
+                Ok(response) => response,
+                Err(e) => return Outcome { result: Err(e.into()), ..outcome },
+            };
+            let status = response.status();
+            outcome.status = Some(status.as_u16());
+
+            if status == StatusCode::TOO_MANY_REQUESTS && outcome.attempts < RATE_LIMIT_RETRIES {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 3 / 2).min(MAX_BACKOFF);
+                continue;
+            }
+
+            outcome.result = match response.text().await {
+                Err(e) => Err(e.into()),
+                Ok(text) if !status.is_success() => Err(ClientError::Api {
+                    method: method.to_string(),
+                    path: path.to_string(),
+                    status: status.as_u16(),
+                    message: error_message(&text),
+                }),
+                Ok(text) if text.trim().is_empty() => Ok(Value::Null),
+                // Some handlers answer with plain text; keep it rather than failing.
+                Ok(text) => Ok(serde_json::from_str(&text).unwrap_or(Value::String(text))),
+            };
+            return outcome;
+        }
+    }
+}
+
+/// Result of [`EdenApiClient::send_with_retries`] plus what the audit log needs.
+struct Outcome {
+    result: Result<Value, ClientError>,
+    status: Option<u16>,
+    attempts: u32,
+}
+
+fn parse<T: serde::de::DeserializeOwned>(value: Value) -> Result<T, ClientError> {
+    Ok(serde_json::from_value(value)?)
+}
+
+/// The service reports failures as `{"error": "..."}`; fall back to the raw body.
+fn error_message(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|value| value.get("error").and_then(Value::as_str).map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_message_prefers_error_field() {
+        assert_eq!(error_message(r#"{"error":"endpoint not found"}"#), "endpoint not found");
+        assert_eq!(error_message("  bad gateway\n"), "bad gateway");
+    }
+
+    #[test]
+    fn urls_are_rooted_at_api_prefix() {
+        let client = EdenApiClient::new("http://localhost:8000/").expect("client");
+        assert_eq!(client.url("/endpoints"), "http://localhost:8000/api/v1/endpoints");
+    }
+}
@@ -0,0 +1,40 @@
+use std::io;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The API answered with a non-success status.
+    #[error("{method} {path} returned {status}: {message}")]
+    Api {
+        method: String,
+        path: String,
+        status: u16,
+        message: String,
+    },
+
+    #[error("unexpected response: {0}")]
+    Decode(#[from] serde_json::Error),
+
+    #[error("no API token; log in first")]
+    Unauthenticated,
+
+    #[error("config: {0}")]
+    Config(String),
+
+    #[error("io: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl ClientError {
+    /// HTTP status of an [`ClientError::Api`] error.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
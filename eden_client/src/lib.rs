@@ -0,0 +1,20 @@
+//! Client for the Eden control-plane API, shared by the command-line tools.
+//!
+//! - [`client`] - [`EdenApiClient`], a thin typed wrapper over `/api/v1`
+//! - [`audit`] - optional JSONL or `tracing` trail of every call
+//! - [`profile`] - named environments from `~/.eden/config.toml`
+//! - [`tls`] - custom CAs and client certificates for mutual TLS
+//! - [`transport`] - proxies, timeouts and keepalive
+//! - [`token`] - JWT cache so tools do not log in on every invocation
+
+pub mod audit;
+pub mod client;
+pub mod error;
+pub mod profile;
+pub mod tls;
+pub mod token;
+pub mod transport;
+pub mod types;
+
+pub use client::EdenApiClient;
+pub use error::ClientError;
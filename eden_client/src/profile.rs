@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ClientError;
+use crate::tls::TlsConfig;
+use crate::transport::TransportConfig;
+
+/// Overrides the directory holding `config.toml` and the token cache.
+pub const CONFIG_DIR_ENV: &str = "EDEN_CONFIG_DIR";
+const CONFIG_FILE: &str = "config.toml";
+const DEFAULT_PROFILE: &str = "default";
+
+/// Connection settings for one Eden environment.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Service root, e.g. `https://eden.staging.example.com`.
+    pub url: Option<String>,
+    pub org: Option<String>,
+    pub username: Option<String>,
+    /// Kept in plain text; make `config.toml` readable only by its owner.
+    pub password: Option<String>,
+    /// Bearer token for creating organizations on deployments that set
+    /// `EDEN_NEW_ORG_TOKEN`.
+    pub new_org_token: Option<String>,
+    #[serde(default)]
+    pub tls: TlsConfig,
+    #[serde(default)]
+    pub transport: TransportConfig,
+}
+
+/// Contents of `~/.eden/config.toml`:
+///
+/// ```toml
+/// default_profile = "dev"
+///
+/// [profiles.dev]
+/// url = "http://localhost:8000"
+/// org = "acme"
+/// username = "admin"
+/// password = "..."
+///
+/// [profiles.dev.tls]
+/// ca_cert = "/etc/eden/dev-ca.pem"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfileConfig {
+    /// Load `config.toml` from `dir`; a missing file is an empty config.
+    pub fn load(dir: &Path) -> Result<Self, ClientError> {
+        let path = dir.join(CONFIG_FILE);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        toml::from_str(&text).map_err(|e| ClientError::Config(format!("{}: {e}", path.display())))
+    }
+
+    /// Resolve a profile by name, falling back to `default_profile` and then
+    /// to `"default"`. Only an explicitly requested profile has to exist.
+    pub fn resolve(&self, name: Option<&str>) -> Result<(String, Profile), ClientError> {
+        if let Some(name) = name {
+            let profile = self.profiles.get(name).ok_or_else(|| ClientError::Config(format!("unknown profile `{name}`")))?;
+            return Ok((name.to_string(), profile.clone()));
+        }
+        let name = self.default_profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+        Ok((name.to_string(), self.profiles.get(name).cloned().unwrap_or_default()))
+    }
+}
+
+/// Load the config from [`config_dir`] and resolve `name` in it.
+pub fn load_profile(name: Option<&str>) -> Result<(String, Profile), ClientError> {
+    ProfileConfig::load(&config_dir()?)?.resolve(name)
+}
+
+/// `$EDEN_CONFIG_DIR`, or `~/.eden`.
+pub fn config_dir() -> Result<PathBuf, ClientError> {
+    if let Some(dir) = env::var_os(CONFIG_DIR_ENV) {
+        return Ok(PathBuf::from(dir));
+    }
+    let dirs = BaseDirs::new().ok_or_else(|| ClientError::Config("cannot determine home directory".to_string()))?;
+    Ok(dirs.home_dir().join(".eden"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        default_profile = "dev"
+
+        [profiles.dev]
+        url = "http://localhost:8000"
+        org = "acme"
+
+        [profiles.prod]
+        url = "https://eden.example.com"
+        username = "ops"
+        password = "hunter2"
+        new_org_token = "bootstrap"
+
+        [profiles.prod.tls]
+        client_cert = "/etc/eden/client.pem"
+        insecure_skip_verify = true
+
+        [profiles.prod.transport]
+        proxy = "socks5h://bastion:1080"
+        tcp_keepalive_secs = 30
+    "#;
+
+    #[test]
+    fn resolves_named_default_and_missing_profiles() {
+        let config: ProfileConfig = toml::from_str(CONFIG).expect("parse config");
+
+        let (name, profile) = config.resolve(None).expect("default profile");
+        assert_eq!(name, "dev");
+        assert_eq!(profile.org.as_deref(), Some("acme"));
+
+        let (_, prod) = config.resolve(Some("prod")).expect("prod profile");
+        assert_eq!(prod.url.as_deref(), Some("https://eden.example.com"));
+        assert_eq!((prod.password.as_deref(), prod.new_org_token.as_deref()), (Some("hunter2"), Some("bootstrap")));
+        assert_eq!(prod.tls.client_cert.as_deref(), Some(Path::new("/etc/eden/client.pem")));
+        assert!(prod.tls.insecure_skip_verify && prod.tls.ca_cert.is_none());
+        assert_eq!(prod.transport.proxy.as_deref(), Some("socks5h://bastion:1080"));
+        assert_eq!((prod.transport.tcp_keepalive_secs, prod.transport.timeout_secs), (Some(30), None));
+
+        assert!(config.resolve(Some("staging")).is_err());
+        assert_eq!(ProfileConfig::default().resolve(None).expect("empty config").0, "default");
+    }
+}
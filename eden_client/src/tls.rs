@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reqwest::{Certificate, ClientBuilder, Identity};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ClientError;
+
+/// TLS settings for reaching the API, e.g. behind a mutual-TLS ingress:
+///
+/// ```toml
+/// [profiles.prod.tls]
+/// ca_cert = "/etc/eden/ca.pem"
+/// client_cert = "/etc/eden/client.pem"
+/// client_key = "/etc/eden/client-key.pem"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// PEM bundle of CAs trusted in addition to the system roots.
+    pub ca_cert: Option<PathBuf>,
+    /// PEM client certificate chain presented to the server.
+    pub client_cert: Option<PathBuf>,
+    /// PEM private key for `client_cert`; may be omitted when `client_cert`
+    /// holds the key as well.
+    pub client_key: Option<PathBuf>,
+    /// Accept any server certificate. For development deployments only.
+    pub insecure_skip_verify: bool,
+}
+
+impl TlsConfig {
+    /// Add these settings to `builder`.
+    pub fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, ClientError> {
+        if let Some(path) = &self.ca_cert {
+            let certs = Certificate::from_pem_bundle(&read(path)?).map_err(|e| invalid(path, e))?;
+            if certs.is_empty() {
+                return Err(ClientError::Config(format!("{}: no certificates found", path.display())));
+            }
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), key) => {
+                let mut pem = read(cert)?;
+                if let Some(key) = key {
+                    pem.push(b'\n');
+                    pem.extend(read(key)?);
+                }
+                builder = builder.identity(Identity::from_pem(&pem).map_err(|e| invalid(cert, e))?);
+            }
+            (None, Some(_)) => return Err(ClientError::Config("client_key is set without client_cert".to_string())),
+            (None, None) => {}
+        }
+        if self.insecure_skip_verify {
+            tracing::warn!("TLS certificate verification is disabled");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        Ok(builder)
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, ClientError> {
+    fs::read(path).map_err(|e| ClientError::Config(format!("{}: {e}", path.display())))
+}
+
+fn invalid(path: &Path, e: reqwest::Error) -> ClientError {
+    ClientError::Config(format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_missing_files_and_a_key_without_a_certificate() {
+        let missing = TlsConfig {
+            ca_cert: Some(PathBuf::from("/nonexistent/ca.pem")),
+            ..TlsConfig::default()
+        };
+        let err = missing.apply(reqwest::Client::builder()).expect_err("missing CA file");
+        assert!(err.to_string().contains("/nonexistent/ca.pem"), "{err}");
+
+        let key_only = TlsConfig {
+            client_key: Some(PathBuf::from("/nonexistent/key.pem")),
+            ..TlsConfig::default()
+        };
+        assert!(key_only.apply(reqwest::Client::builder()).is_err());
+
+        assert!(TlsConfig::default().apply(reqwest::Client::builder()).is_ok());
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::ClientError;
+
+const CACHE_FILE: &str = "credentials.json";
+/// Tokens this close to expiry are treated as expired so a call does not race
+/// the deadline.
+const EXPIRY_MARGIN_SECS: u64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedToken {
+    pub url: String,
+    pub org: String,
+    pub token: String,
+}
+
+/// Per-profile JWTs kept between invocations in `credentials.json`.
+#[derive(Debug, Default)]
+pub struct TokenCache {
+    path: PathBuf,
+    tokens: BTreeMap<String, CachedToken>,
+}
+
+impl TokenCache {
+    pub fn load(dir: &Path) -> Result<Self, ClientError> {
+        let path = dir.join(CACHE_FILE);
+        let tokens = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { path, tokens })
+    }
+
+    /// The cached token for `profile`, if it was issued by the same service and
+    /// organization and is not about to expire.
+    pub fn get(&self, profile: &str, url: &str, org: &str) -> Option<&str> {
+        let cached = self.tokens.get(profile)?;
+        if cached.url != url || cached.org != org {
+            return None;
+        }
+        let fresh = jwt_expiry(&cached.token).is_none_or(|exp| exp > now_secs() + EXPIRY_MARGIN_SECS);
+        fresh.then_some(cached.token.as_str())
+    }
+
+    pub fn insert(&mut self, profile: &str, token: CachedToken) {
+        self.tokens.insert(profile.to_string(), token);
+    }
+
+    pub fn remove(&mut self, profile: &str) -> bool {
+        self.tokens.remove(profile).is_some()
+    }
+
+    /// Write the cache, readable by the current user only. The tokens go to a
+    /// new file created with those permissions, which then replaces the cache,
+    /// so they are never readable by other users.
+    pub fn save(&self) -> Result<(), ClientError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let staging = self.path.with_extension("json.tmp");
+        if let Err(e) = fs::remove_file(&staging)
+            && e.kind() != io::ErrorKind::NotFound
+        {
+            return Err(e.into());
+        }
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&staging)?.write_all(&serde_json::to_vec_pretty(&self.tokens)?)?;
+        fs::rename(&staging, &self.path)?;
+        Ok(())
+    }
+}
+
+/// The `exp` claim of a JWT, without verifying its signature.
+pub fn jwt_expiry(token: &str) -> Option<u64> {
+    let payload = token.split('.').nth(1)?;
+    let claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+    claims.get("exp")?.as_u64()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt_with_exp(exp: u64) -> String {
+        let claims = URL_SAFE_NO_PAD.encode(format!(r#"{{"sub":"admin","exp":{exp}}}"#));
+        format!("e30.{claims}.sig")
+    }
+
+    #[test]
+    fn reads_exp_claim() {
+        assert_eq!(jwt_expiry(&jwt_with_exp(1_700_000_000)), Some(1_700_000_000));
+        assert_eq!(jwt_expiry("not-a-jwt"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn saved_cache_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("eden-token-cache-{}", std::process::id()));
+        let mut cache = TokenCache::load(&dir).expect("empty cache");
+        let token = CachedToken {
+            url: "http://localhost:8000".to_string(),
+            org: "acme".to_string(),
+            token: "jwt".to_string(),
+        };
+        cache.insert("dev", token.clone());
+        cache.save().expect("first save");
+        fs::set_permissions(dir.join(CACHE_FILE), fs::Permissions::from_mode(0o644)).expect("chmod");
+        cache.save().expect("overwrite");
+
+        let mode = fs::metadata(dir.join(CACHE_FILE)).expect("cache file").permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(TokenCache::load(&dir).expect("saved cache").tokens.get("dev"), Some(&token));
+        fs::remove_dir_all(&dir).expect("cleanup");
+    }
+
+    #[test]
+    fn expired_or_foreign_tokens_are_not_returned() {
+        let url = "http://localhost:8000";
+        let mut cache = TokenCache::default();
+        cache.insert(
+            "dev",
+            CachedToken {
+                url: url.to_string(),
+                org: "acme".to_string(),
+                token: jwt_with_exp(now_secs() + 3600),
+            },
+        );
+        cache.insert(
+            "old",
+            CachedToken {
+                url: url.to_string(),
+                org: "acme".to_string(),
+                token: jwt_with_exp(now_secs() + 10),
+            },
+        );
+
+        assert!(cache.get("dev", url, "acme").is_some());
+        assert!(cache.get("dev", url, "other").is_none());
+        assert!(cache.get("dev", "http://elsewhere", "acme").is_none());
+        assert!(cache.get("old", url, "acme").is_none());
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use reqwest::{ClientBuilder, NoProxy, Proxy};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ClientError;
+
+/// Applies to the whole request, including retries of the body read.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// How API requests reach the service, for deployments behind a bastion or
+/// an egress proxy:
+///
+/// ```toml
+/// [profiles.prod.transport]
+/// proxy = "socks5h://bastion.internal:1080"
+/// connect_timeout_secs = 5
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransportConfig {
+    /// `http://`, `https://`, `socks5://` or `socks5h://` proxy for every
+    /// request. When unset, `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and
+    /// `NO_PROXY` from the environment apply.
+    pub proxy: Option<String>,
+    /// Comma-separated hosts and CIDRs that bypass `proxy`, in `NO_PROXY`
+    /// syntax.
+    pub no_proxy: Option<String>,
+    /// Whole-request timeout; 30 seconds when unset.
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    /// TCP keepalive probe interval, so idle connections through NAT or a
+    /// bastion are not silently dropped.
+    pub tcp_keepalive_secs: Option<u64>,
+    /// How long an idle pooled connection is kept for reuse.
+    pub pool_idle_timeout_secs: Option<u64>,
+}
+
+impl TransportConfig {
+    /// Add these settings to `builder`.
+    pub fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, ClientError> {
+        builder = builder.timeout(Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)));
+        if let Some(url) = &self.proxy {
+            let proxy = Proxy::all(url).map_err(|e| ClientError::Config(format!("invalid proxy `{url}`: {e}")))?;
+            builder = builder.proxy(proxy.no_proxy(self.no_proxy.as_deref().and_then(NoProxy::from_string)));
+        } else if self.no_proxy.is_some() {
+            return Err(ClientError::Config(
+                "no_proxy is set without proxy; use NO_PROXY for environment proxies".to_string(),
+            ));
+        }
+        if let Some(secs) = self.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.tcp_keepalive_secs {
+            builder = builder.tcp_keepalive(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.pool_idle_timeout_secs {
+            builder = builder.pool_idle_timeout(Duration::from_secs(secs));
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_malformed_proxies() {
+        let config = |proxy: &str| TransportConfig { proxy: Some(proxy.to_string()), ..TransportConfig::default() };
+        assert!(config("http://proxy.internal:3128").apply(reqwest::Client::builder()).is_ok());
+        assert!(config("not a url").apply(reqwest::Client::builder()).is_err());
+
+        let stray = TransportConfig {
+            no_proxy: Some("localhost".to_string()),
+            ..TransportConfig::default()
+        };
+        assert!(stray.apply(reqwest::Client::builder()).is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod log;
+pub mod record;
+pub mod replay;
+pub mod resp;
@@ -0,0 +1,140 @@
+//! The capture file: a gzip stream of timestamped command frames.
+//!
+//! After an 8-byte magic, each record is a little-endian header (offset from
+//! the start of recording in microseconds as `u64`, connection number as
+//! `u32`, frame length as `u32`) followed by the raw RESP frame.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+const MAGIC: &[u8; 8] = b"TAPROUT1";
+const HEADER_LEN: usize = 16;
+
+/// One client command as it arrived at the proxy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// Time since recording started.
+    pub offset: Duration,
+    /// Client connection the command arrived on, numbered from 0.
+    pub connection: u32,
+    pub frame: Vec<u8>,
+}
+
+pub struct LogWriter<W: Write> {
+    encoder: GzEncoder<W>,
+}
+
+impl LogWriter<BufWriter<File>> {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> LogWriter<W> {
+    pub fn new(inner: W) -> io::Result<Self> {
+        let mut encoder = GzEncoder::new(inner, Compression::default());
+        encoder.write_all(MAGIC)?;
+        Ok(Self { encoder })
+    }
+
+    pub fn write(&mut self, record: &Record) -> io::Result<()> {
+        let offset = u64::try_from(record.offset.as_micros()).unwrap_or(u64::MAX);
+        let len = u32::try_from(record.frame.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame over 4 GiB"))?;
+        self.encoder.write_all(&offset.to_le_bytes())?;
+        self.encoder.write_all(&record.connection.to_le_bytes())?;
+        self.encoder.write_all(&len.to_le_bytes())?;
+        self.encoder.write_all(&record.frame)
+    }
+
+    /// Write the gzip trailer and flush. A log that is not finished cannot be
+    /// read to the end.
+    pub fn finish(self) -> io::Result<W> {
+        let mut inner = self.encoder.finish()?;
+        inner.flush()?;
+        Ok(inner)
+    }
+}
+
+pub struct LogReader<R: Read> {
+    decoder: GzDecoder<R>,
+}
+
+impl LogReader<BufReader<File>> {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> LogReader<R> {
+    pub fn new(inner: R) -> io::Result<Self> {
+        let mut decoder = GzDecoder::new(inner);
+        let mut magic = [0; MAGIC.len()];
+        decoder.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an eden-taproute capture"));
+        }
+        Ok(Self { decoder })
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<Record>> {
+        let mut header = [0; HEADER_LEN];
+        let mut filled = 0;
+        while filled < HEADER_LEN {
+            match self.decoder.read(&mut header[filled..])? {
+                0 if filled == 0 => return Ok(None),
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                n => filled += n,
+            }
+        }
+        let offset = u64::from_le_bytes(header[0..8].try_into().expect("8-byte field"));
+        let connection = u32::from_le_bytes(header[8..12].try_into().expect("4-byte field"));
+        let len = u32::from_le_bytes(header[12..16].try_into().expect("4-byte field"));
+        let mut frame = vec![0; len as usize];
+        self.decoder.read_exact(&mut frame)?;
+        Ok(Some(Record { offset: Duration::from_micros(offset), connection, frame }))
+    }
+}
+
+impl<R: Read> Iterator for LogReader<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let records = vec![
+            Record {
+                offset: Duration::from_micros(0),
+                connection: 0,
+                frame: b"*1\r\n$4\r\nPING\r\n".to_vec(),
+            },
+            Record {
+                offset: Duration::from_millis(1500),
+                connection: 7,
+                frame: b"GET k\r\n".to_vec(),
+            },
+        ];
+        let mut writer = LogWriter::new(Vec::new()).expect("writer");
+        for record in &records {
+            writer.write(record).expect("write");
+        }
+        let bytes = writer.finish().expect("finish");
+
+        let read: Vec<Record> = LogReader::new(bytes.as_slice()).expect("reader").collect::<io::Result<_>>().expect("read");
+        assert_eq!(read, records);
+        assert!(LogReader::new(&b"not gzip"[..]).is_err());
+    }
+}
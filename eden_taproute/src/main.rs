@@ -0,0 +1,123 @@
+use std::io;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+
+use eden_taproute::record::{self, RecordConfig};
+use eden_taproute::replay::{self, ReplayConfig};
+
+/// Record Redis traffic through a transparent RESP proxy and replay it
+/// against another endpoint.
+#[derive(Parser)]
+#[command(name = "eden-taproute")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Proxy clients to --upstream and record their commands until Ctrl-C.
+    Record {
+        /// Address clients connect to instead of the Redis endpoint.
+        #[arg(long, default_value = "127.0.0.1:6380")]
+        listen: String,
+        /// Redis endpoint to forward to, as host:port.
+        #[arg(long)]
+        upstream: String,
+        /// Capture file to write (gzip-compressed).
+        #[arg(long, short)]
+        output: PathBuf,
+        /// Commands buffered for the log writer before new ones are dropped.
+        #[arg(long, default_value_t = 65_536)]
+        queue: usize,
+    },
+    /// Replay a capture against --target.
+    Replay {
+        /// Capture file written by `record`.
+        #[arg(long, short)]
+        input: PathBuf,
+        /// Redis endpoint or interlay to replay against, as host:port.
+        #[arg(long)]
+        target: String,
+        /// Speed multiplier; 0 replays as fast as the target accepts.
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+        /// ACL user for the AUTH sent on each replay connection.
+        #[arg(long)]
+        username: Option<String>,
+        /// Password for the AUTH sent on each replay connection.
+        #[arg(long, env = "TAPROUTE_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+        /// Seconds to wait for outstanding replies after the last command.
+        #[arg(long, default_value_t = 5)]
+        drain_timeout_secs: u64,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("error: {e}");
+        process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> io::Result<()> {
+    match cli.command {
+        Command::Record { listen, upstream, output, queue } => {
+            let config = RecordConfig { listen, upstream, output, queue };
+            eprintln!(
+                "eden-taproute: proxying {} -> {}, recording to {}; Ctrl-C to stop",
+                config.listen,
+                config.upstream,
+                config.output.display()
+            );
+            let summary = record::record(&config, async {
+                if let Err(e) = tokio::signal::ctrl_c().await {
+                    eprintln!("eden-taproute: cannot listen for Ctrl-C: {e}");
+                    std::future::pending::<()>().await;
+                }
+            })
+            .await?;
+            eprintln!(
+                "done: connections={} commands={} credentials_skipped={} dropped={} elapsed={:.1}s",
+                summary.connections, summary.commands, summary.credentials_skipped, summary.dropped, summary.elapsed_secs
+            );
+            print_json(&summary);
+        }
+        Command::Replay { input, target, speed, username, password, drain_timeout_secs } => {
+            let config = ReplayConfig {
+                input,
+                target,
+                speed,
+                username,
+                password,
+                drain_timeout: Duration::from_secs(drain_timeout_secs),
+            };
+            eprintln!("eden-taproute: replaying {} against {} at {}x", config.input.display(), config.target, config.speed);
+            let summary = replay::replay(&config).await?;
+            eprintln!(
+                "done: connections={} commands={} replies={} errors={} unanswered={} failed_connections={} elapsed={:.1}s",
+                summary.connections,
+                summary.commands,
+                summary.replies,
+                summary.errors,
+                summary.unanswered,
+                summary.failed_connections,
+                summary.elapsed_secs
+            );
+            print_json(&summary);
+        }
+    }
+    Ok(())
+}
+
+fn print_json(summary: &impl Serialize) {
+    let json = serde_json::to_string_pretty(summary).expect("JSON serialization");
+    println!("{json}");
+}
@@ -0,0 +1,209 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinSet;
+
+use crate::log::{LogWriter, Record};
+use crate::resp;
+
+const READ_BUFFER: usize = 16 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct RecordConfig {
+    /// Address clients connect to instead of the Redis endpoint.
+    pub listen: String,
+    /// Redis endpoint traffic is forwarded to, as host:port.
+    pub upstream: String,
+    pub output: PathBuf,
+    /// Commands buffered for the log writer. When the buffer is full commands
+    /// are dropped from the log rather than delaying client traffic.
+    pub queue: usize,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    connections: AtomicU64,
+    commands: AtomicU64,
+    credentials_skipped: AtomicU64,
+    dropped: AtomicU64,
+    unframed_connections: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordSummary {
+    pub connections: u64,
+    /// Commands written to the log.
+    pub commands: u64,
+    /// AUTH and HELLO ... AUTH commands, which are forwarded but not logged.
+    pub credentials_skipped: u64,
+    /// Commands lost because the log writer fell behind.
+    pub dropped: u64,
+    /// Connections whose traffic stopped being logged after a framing error.
+    pub unframed_connections: u64,
+    pub elapsed_secs: f64,
+}
+
+/// Proxy every client of `config.listen` to the upstream, logging the
+/// commands they send, until `shutdown` completes. Open connections are
+/// closed on shutdown so the log can be finished.
+pub async fn record(config: &RecordConfig, shutdown: impl Future<Output = ()>) -> io::Result<RecordSummary> {
+    let listener = TcpListener::bind(&config.listen).await?;
+    let writer = LogWriter::create(&config.output)?;
+    let (records, queue) = mpsc::channel(config.queue.max(1));
+    let writer = tokio::task::spawn_blocking(move || write_log(writer, queue));
+
+    let counters = Arc::new(Counters::default());
+    let started = Instant::now();
+    let mut connections = JoinSet::new();
+    tokio::pin!(shutdown);
+    loop {
+        let (client, peer) = tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    eprintln!("eden-taproute: accept failed: {e}");
+                    continue;
+                }
+            },
+        };
+        let id = counters.connections.fetch_add(1, Ordering::Relaxed) as u32;
+        let session = Session {
+            id,
+            started,
+            records: records.clone(),
+            counters: Arc::clone(&counters),
+        };
+        let upstream = config.upstream.clone();
+        connections.spawn(async move {
+            if let Err(e) = session.proxy(client, &upstream).await {
+                eprintln!("eden-taproute: connection {id} from {peer}: {e}");
+            }
+        });
+        // Reap finished connections so the set does not grow without bound.
+        while connections.try_join_next().is_some() {}
+    }
+
+    connections.shutdown().await;
+    drop(records);
+    writer.await.map_err(io::Error::other)??;
+
+    Ok(RecordSummary {
+        connections: counters.connections.load(Ordering::Relaxed),
+        commands: counters.commands.load(Ordering::Relaxed),
+        credentials_skipped: counters.credentials_skipped.load(Ordering::Relaxed),
+        dropped: counters.dropped.load(Ordering::Relaxed),
+        unframed_connections: counters.unframed_connections.load(Ordering::Relaxed),
+        elapsed_secs: started.elapsed().as_secs_f64(),
+    })
+}
+
+fn write_log(mut writer: LogWriter<std::io::BufWriter<std::fs::File>>, mut queue: mpsc::Receiver<Record>) -> io::Result<()> {
+    while let Some(record) = queue.blocking_recv() {
+        writer.write(&record)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+struct Session {
+    id: u32,
+    started: Instant,
+    records: mpsc::Sender<Record>,
+    counters: Arc<Counters>,
+}
+
+impl Session {
+    /// Forward bytes in both directions unchanged. Client bytes are sent
+    /// upstream before they are framed, so logging never delays a command.
+    async fn proxy(&self, client: TcpStream, upstream: &str) -> io::Result<()> {
+        let server = TcpStream::connect(upstream).await?;
+        client.set_nodelay(true)?;
+        server.set_nodelay(true)?;
+        let (mut client_read, mut client_write) = client.into_split();
+        let (mut server_read, mut server_write) = server.into_split();
+
+        let requests = async {
+            let mut buf = vec![0; READ_BUFFER];
+            let mut pending = Vec::new();
+            let mut framing = true;
+            loop {
+                let n = client_read.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                server_write.write_all(&buf[..n]).await?;
+                if framing {
+                    pending.extend_from_slice(&buf[..n]);
+                    framing = self.capture(&mut pending);
+                }
+            }
+            server_write.shutdown().await
+        };
+        let replies = async {
+            tokio::io::copy(&mut server_read, &mut client_write).await?;
+            client_write.shutdown().await
+        };
+        tokio::try_join!(requests, replies)?;
+        Ok(())
+    }
+
+    /// Log the complete frames in `pending`. Returns false once the stream
+    /// cannot be framed; the connection is still proxied but no longer logged.
+    fn capture(&self, pending: &mut Vec<u8>) -> bool {
+        let frames = match resp::drain_frames(pending) {
+            Ok(frames) => frames,
+            Err(e) => {
+                eprintln!("eden-taproute: connection {}: {e}; no longer recording it", self.id);
+                self.counters.unframed_connections.fetch_add(1, Ordering::Relaxed);
+                pending.clear();
+                return false;
+            }
+        };
+        let offset = self.started.elapsed();
+        for frame in frames {
+            if is_credential(&frame) {
+                self.counters.credentials_skipped.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            let counter = match self.records.try_send(Record { offset, connection: self.id, frame }) {
+                Ok(()) => &self.counters.commands,
+                Err(TrySendError::Full(_)) | Err(TrySendError::Closed(_)) => &self.counters.dropped,
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        true
+    }
+}
+
+/// Whether a command carries a password: `AUTH` or `HELLO ... AUTH`.
+pub fn is_credential(frame: &[u8]) -> bool {
+    let Some(args) = resp::command_args(frame) else {
+        return false;
+    };
+    match args.split_first() {
+        Some((name, _)) if name.eq_ignore_ascii_case(b"AUTH") => true,
+        Some((name, rest)) if name.eq_ignore_ascii_case(b"HELLO") => rest.iter().any(|arg| arg.eq_ignore_ascii_case(b"AUTH")),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_are_not_logged() {
+        assert!(is_credential(b"*2\r\n$4\r\nauth\r\n$6\r\nsecret\r\n"));
+        assert!(is_credential(b"HELLO 3 AUTH default secret\r\n"));
+        assert!(!is_credential(b"HELLO 3\r\n"));
+        assert!(!is_credential(b"*2\r\n$3\r\nGET\r\n$4\r\nAUTH\r\n"));
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinSet;
+
+use crate::log::{LogReader, Record};
+use crate::resp;
+
+const READ_BUFFER: usize = 16 * 1024;
+const SESSION_QUEUE: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    pub input: PathBuf,
+    /// Redis endpoint to replay against, as host:port.
+    pub target: String,
+    /// Playback speed relative to the recording; 2.0 replays twice as fast.
+    /// Zero sends every command as soon as the previous one is written.
+    pub speed: f64,
+    /// Sent as `AUTH` on every replay connection, since recordings never
+    /// contain credentials.
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// How long to wait for outstanding replies once a connection has sent
+    /// its last command.
+    pub drain_timeout: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    commands: AtomicU64,
+    replies: AtomicU64,
+    errors: AtomicU64,
+    unanswered: AtomicU64,
+    failed_connections: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplaySummary {
+    pub connections: u64,
+    pub commands: u64,
+    pub replies: u64,
+    /// Error replies from the target.
+    pub errors: u64,
+    /// Commands still unanswered when the drain timeout expired.
+    pub unanswered: u64,
+    /// Connections that could not connect, authenticate or write.
+    pub failed_connections: u64,
+    /// Span of the recording that was replayed.
+    pub recorded_secs: f64,
+    pub elapsed_secs: f64,
+}
+
+/// When a command recorded at `offset` is due. A tiny `speed` stretches the
+/// offset past what `Instant` can hold, which is rejected rather than
+/// sleeping forever.
+fn send_at(started: Instant, offset: Duration, speed: f64) -> io::Result<Instant> {
+    Duration::try_from_secs_f64(offset.as_secs_f64() / speed)
+        .ok()
+        .and_then(|delay| started.checked_add(delay))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("speed {speed} is too slow to schedule offset {offset:?}")))
+}
+
+/// Replay a capture against `config.target`, one connection per recorded
+/// client connection. Commands keep their recorded order and, unless
+/// `speed` is zero, their recorded spacing divided by `speed`.
+pub async fn replay(config: &ReplayConfig) -> io::Result<ReplaySummary> {
+    if !config.speed.is_finite() || config.speed < 0.0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "speed must be a non-negative number"));
+    }
+    let reader = LogReader::open(&config.input)?;
+    let (records_tx, mut records) = mpsc::channel(SESSION_QUEUE);
+    let read_log = tokio::task::spawn_blocking(move || {
+        for record in reader {
+            if records_tx.blocking_send(record).is_err() {
+                break;
+            }
+        }
+    });
+
+    let auth = auth_command(config.username.as_deref(), config.password.as_deref());
+    let counters = Arc::new(Counters::default());
+    let started = Instant::now();
+    let mut sessions: HashMap<u32, mpsc::Sender<Vec<u8>>> = HashMap::new();
+    let mut tasks = JoinSet::new();
+    let mut recorded = Duration::ZERO;
+
+    while let Some(record) = records.recv().await {
+        let Record { offset, connection, frame } = record?;
+        if config.speed > 0.0 {
+            tokio::time::sleep_until(send_at(started, offset, config.speed)?.into()).await;
+        }
+        recorded = recorded.max(offset);
+        let session = sessions.entry(connection).or_insert_with(|| {
+            let (commands, queue) = mpsc::channel(SESSION_QUEUE);
+            let target = config.target.clone();
+            let auth = auth.clone();
+            let counters = Arc::clone(&counters);
+            let drain_timeout = config.drain_timeout;
+            tasks.spawn(async move {
+                if let Err(e) = run_session(&target, auth, queue, &counters, drain_timeout).await {
+                    eprintln!("eden-taproute: replay of connection {connection}: {e}");
+                    counters.failed_connections.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+            commands
+        });
+        // A failed session has already been counted; its remaining commands are skipped.
+        let _ = session.send(frame).await;
+    }
+    read_log.await.map_err(io::Error::other)?;
+
+    let connections = sessions.len() as u64;
+    drop(sessions);
+    while tasks.join_next().await.is_some() {}
+
+    Ok(ReplaySummary {
+        connections,
+        commands: counters.commands.load(Ordering::Relaxed),
+        replies: counters.replies.load(Ordering::Relaxed),
+        errors: counters.errors.load(Ordering::Relaxed),
+        unanswered: counters.unanswered.load(Ordering::Relaxed),
+        failed_connections: counters.failed_connections.load(Ordering::Relaxed),
+        recorded_secs: recorded.as_secs_f64(),
+        elapsed_secs: started.elapsed().as_secs_f64(),
+    })
+}
+
+async fn run_session(
+    target: &str,
+    auth: Option<Vec<u8>>,
+    mut commands: mpsc::Receiver<Vec<u8>>,
+    counters: &Counters,
+    drain_timeout: Duration,
+) -> io::Result<()> {
+    let mut stream = TcpStream::connect(target).await?;
+    stream.set_nodelay(true)?;
+    if let Some(auth) = auth {
+        stream.write_all(&auth).await?;
+        let reply = read_one(&mut stream).await?;
+        if resp::is_error(&reply) {
+            let message = String::from_utf8_lossy(&reply);
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("AUTH failed: {}", message.trim())));
+        }
+    }
+
+    let (reader, mut writer) = stream.into_split();
+    let (sent_tx, sent) = watch::channel(None);
+    let replies = read_replies(reader, sent, counters, drain_timeout);
+    let requests = async move {
+        let mut count = 0u64;
+        while let Some(frame) = commands.recv().await {
+            writer.write_all(&frame).await?;
+            counters.commands.fetch_add(1, Ordering::Relaxed);
+            count += 1;
+        }
+        // The reader is gone only if it already failed, which try_join reports.
+        let _ = sent_tx.send(Some(count));
+        Ok::<_, io::Error>(writer)
+    };
+    // Keep the write half open until all replies are read: Redis may drop
+    // pending replies once it sees the client close.
+    tokio::try_join!(requests, replies)?;
+    Ok(())
+}
+
+/// Count replies until one has arrived for every command sent, the drain
+/// timeout expires after the last command, or the target closes.
+async fn read_replies(
+    mut reader: OwnedReadHalf,
+    mut sent: watch::Receiver<Option<u64>>,
+    counters: &Counters,
+    drain_timeout: Duration,
+) -> io::Result<()> {
+    let mut buf = vec![0; READ_BUFFER];
+    let mut pending = Vec::new();
+    let mut received = 0u64;
+    loop {
+        let expected = *sent.borrow_and_update();
+        let n = match expected {
+            Some(expected) if received >= expected => return Ok(()),
+            Some(expected) => match tokio::time::timeout(drain_timeout, reader.read(&mut buf)).await {
+                Ok(n) => n?,
+                Err(_) => {
+                    counters.unanswered.fetch_add(expected - received, Ordering::Relaxed);
+                    return Ok(());
+                }
+            },
+            None => tokio::select! {
+                n = reader.read(&mut buf) => n?,
+                changed = sent.changed() => {
+                    if changed.is_err() {
+                        return Ok(());
+                    }
+                    continue;
+                }
+            },
+        };
+        if n == 0 {
+            if let Some(expected) = expected {
+                counters.unanswered.fetch_add(expected.saturating_sub(received), Ordering::Relaxed);
+            }
+            return Ok(());
+        }
+        pending.extend_from_slice(&buf[..n]);
+        let frames = resp::drain_frames(&mut pending).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        for frame in frames {
+            received += 1;
+            counters.replies.fetch_add(1, Ordering::Relaxed);
+            if resp::is_error(&frame) {
+                counters.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+async fn read_one(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut pending = Vec::new();
+    let mut buf = vec![0; READ_BUFFER];
+    loop {
+        if let Some(len) = resp::frame_len(&pending).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))? {
+            pending.truncate(len);
+            return Ok(pending);
+        }
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        pending.extend_from_slice(&buf[..n]);
+    }
+}
+
+fn auth_command(username: Option<&str>, password: Option<&str>) -> Option<Vec<u8>> {
+    let password = password?;
+    let args: Vec<&str> = match username {
+        Some(username) => vec!["AUTH", username, password],
+        None => vec!["AUTH", password],
+    };
+    let mut command = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        command.extend_from_slice(format!("${}\r\n{arg}\r\n", arg.len()).as_bytes());
+    }
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::log::LogWriter;
+
+    /// Answers each command with `+OK`, or an error for `FAIL`.
+    async fn fake_redis(listener: TcpListener) {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                return;
+            };
+            tokio::spawn(async move {
+                let mut pending = Vec::new();
+                let mut buf = vec![0; READ_BUFFER];
+                while let Ok(n @ 1..) = stream.read(&mut buf).await {
+                    pending.extend_from_slice(&buf[..n]);
+                    for frame in resp::drain_frames(&mut pending).expect("valid RESP") {
+                        let args = resp::command_args(&frame).expect("command");
+                        let reply: &[u8] = if args[0] == b"FAIL" { b"-ERR failed\r\n" } else { b"+OK\r\n" };
+                        stream.write_all(reply).await.expect("reply");
+                    }
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn send_at_rejects_speeds_that_overflow() {
+        let started = Instant::now();
+        let offset = Duration::from_secs(2);
+        assert_eq!(send_at(started, offset, 2.0).expect("schedule"), started + Duration::from_secs(1));
+        assert_eq!(send_at(started, Duration::ZERO, 1e-20).expect("schedule"), started);
+        let err = send_at(started, offset, 1e-20).expect_err("overflow");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[tokio::test]
+    async fn replay_sends_each_connection_and_counts_replies() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let target = listener.local_addr().expect("addr").to_string();
+        tokio::spawn(fake_redis(listener));
+
+        let dir = std::env::temp_dir().join(format!("eden-taproute-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let input = dir.join("capture.tap.gz");
+        let mut writer = LogWriter::create(&input).expect("create");
+        for (millis, connection, frame) in [(0, 0, "SET a 1\r\n"), (5, 1, "FAIL\r\n"), (10, 0, "GET a\r\n")] {
+            let record = Record {
+                offset: Duration::from_millis(millis),
+                connection,
+                frame: frame.into(),
+            };
+            writer.write(&record).expect("write");
+        }
+        writer.finish().expect("finish");
+
+        let config = ReplayConfig {
+            input,
+            target,
+            speed: 0.0,
+            username: None,
+            password: Some("secret".to_string()),
+            drain_timeout: Duration::from_secs(5),
+        };
+        let summary = replay(&config).await.expect("replay");
+        std::fs::remove_dir_all(&dir).expect("cleanup");
+
+        assert_eq!((summary.connections, summary.commands, summary.replies), (2, 3, 3));
+        assert_eq!((summary.errors, summary.unanswered, summary.failed_connections), (1, 0, 0));
+        assert!((summary.recorded_secs - 0.01).abs() < 1e-9);
+    }
+}
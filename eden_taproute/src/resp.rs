@@ -0,0 +1,141 @@
+//! Just enough RESP to split a byte stream into whole frames.
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FrameError {
+    #[error("invalid RESP length: {0:?}")]
+    InvalidLength(String),
+    #[error("RESP nesting deeper than {MAX_DEPTH}")]
+    TooDeep,
+}
+
+const MAX_DEPTH: usize = 64;
+
+/// Length of the first complete frame in `buf`, or `None` if more bytes are
+/// needed. Besides RESP2/RESP3 values this accepts inline commands, the
+/// newline-terminated form `redis-cli` and telnet sessions send.
+pub fn frame_len(buf: &[u8]) -> Result<Option<usize>, FrameError> {
+    value_end(buf, 0, 0)
+}
+
+/// Split complete frames off the front of `buf`, leaving any partial frame.
+pub fn drain_frames(buf: &mut Vec<u8>) -> Result<Vec<Vec<u8>>, FrameError> {
+    let mut frames = Vec::new();
+    let mut start = 0;
+    while let Some(len) = frame_len(&buf[start..])? {
+        frames.push(buf[start..start + len].to_vec());
+        start += len;
+    }
+    buf.drain(..start);
+    Ok(frames)
+}
+
+/// Arguments of a command sent as an array of bulk strings or inline.
+pub fn command_args(frame: &[u8]) -> Option<Vec<&[u8]>> {
+    if frame.first() != Some(&b'*') {
+        let line = frame.strip_suffix(b"\n")?;
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        return Some(line.split(|byte| byte.is_ascii_whitespace()).filter(|arg| !arg.is_empty()).collect());
+    }
+    let (count, mut pos) = read_line(frame, 1)?;
+    let count: usize = parse_int(count).ok()?.try_into().ok()?;
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+        if frame.get(pos) != Some(&b'$') {
+            return None;
+        }
+        let (len, data) = read_line(frame, pos + 1)?;
+        let len: usize = parse_int(len).ok()?.try_into().ok()?;
+        args.push(frame.get(data..data + len)?);
+        pos = data + len + 2;
+    }
+    Some(args)
+}
+
+/// Whether a reply frame is an error.
+pub fn is_error(frame: &[u8]) -> bool {
+    matches!(frame.first(), Some(b'-' | b'!'))
+}
+
+fn value_end(buf: &[u8], pos: usize, depth: usize) -> Result<Option<usize>, FrameError> {
+    if depth > MAX_DEPTH {
+        return Err(FrameError::TooDeep);
+    }
+    let Some(&tag) = buf.get(pos) else {
+        return Ok(None);
+    };
+    match tag {
+        b'+' | b'-' | b':' | b',' | b'(' | b'#' | b'_' => Ok(read_line(buf, pos + 1).map(|(_, end)| end)),
+        b'$' | b'!' | b'=' => {
+            let Some((len, data)) = read_line(buf, pos + 1) else {
+                return Ok(None);
+            };
+            let len = parse_int(len)?;
+            if len < 0 {
+                return Ok(Some(data));
+            }
+            let end = data + len as usize + 2;
+            Ok((buf.len() >= end).then_some(end))
+        }
+        b'*' | b'~' | b'>' | b'%' | b'|' => {
+            let Some((len, mut end)) = read_line(buf, pos + 1) else {
+                return Ok(None);
+            };
+            let len = parse_int(len)?;
+            let elements = if matches!(tag, b'%' | b'|') { len.saturating_mul(2) } else { len };
+            for _ in 0..elements.max(0) {
+                match value_end(buf, end, depth + 1)? {
+                    Some(next) => end = next,
+                    None => return Ok(None),
+                }
+            }
+            Ok(Some(end))
+        }
+        _ => Ok(buf[pos..].iter().position(|&byte| byte == b'\n').map(|newline| pos + newline + 1)),
+    }
+}
+
+/// The line starting at `pos`, without its CRLF, and the offset after it.
+fn read_line(buf: &[u8], pos: usize) -> Option<(&[u8], usize)> {
+    let rest = buf.get(pos..)?;
+    let cr = rest.windows(2).position(|pair| pair == b"\r\n")?;
+    Some((&rest[..cr], pos + cr + 2))
+}
+
+fn parse_int(digits: &[u8]) -> Result<i64, FrameError> {
+    std::str::from_utf8(digits)
+        .ok()
+        .and_then(|digits| digits.parse().ok())
+        .ok_or_else(|| FrameError::InvalidLength(String::from_utf8_lossy(digits).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_wait_for_complete_values() {
+        let set = b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$5\r\nhello\r\n";
+        assert_eq!(frame_len(set), Ok(Some(set.len())));
+        for cut in 0..set.len() {
+            assert_eq!(frame_len(&set[..cut]), Ok(None), "cut at {cut}");
+        }
+        assert_eq!(frame_len(b"$-1\r\n+OK\r\n"), Ok(Some(5)));
+        assert_eq!(frame_len(b"%1\r\n+a\r\n:1\r\n"), Ok(Some(12)));
+        assert_eq!(frame_len(b"PING\r\n"), Ok(Some(6)));
+        assert!(frame_len(b"$x\r\n").is_err());
+    }
+
+    #[test]
+    fn drain_keeps_the_partial_tail() {
+        let mut buf = b"+OK\r\n:1\r\n$5\r\nhel".to_vec();
+        assert_eq!(drain_frames(&mut buf), Ok(vec![b"+OK\r\n".to_vec(), b":1\r\n".to_vec()]));
+        assert_eq!(buf, b"$5\r\nhel");
+    }
+
+    #[test]
+    fn command_args_reads_both_forms() {
+        let expected: Vec<&[u8]> = vec![b"AUTH", b"secret"];
+        assert_eq!(command_args(b"*2\r\n$4\r\nAUTH\r\n$6\r\nsecret\r\n"), Some(expected.clone()));
+        assert_eq!(command_args(b"AUTH  secret\r\n"), Some(expected));
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::report::Report;
+
+/// Which keyspace a SCAN cursor belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pass {
+    /// Walking the source and comparing each key against the destination.
+    Source,
+    /// Walking the destination looking for keys the source does not have.
+    Dest,
+}
+
+/// The keyspace a run covers. A cursor is only meaningful for the same
+/// instances, pattern and sampling, so a checkpoint resumes only a run with
+/// an equal scope.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scope {
+    /// Source and destination with passwords redacted.
+    pub source: String,
+    pub dest: String,
+    pub pattern: Option<String>,
+    pub sample_rate: f64,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "source={} dest={} match={} sample_rate={}",
+            self.source,
+            self.dest,
+            self.pattern.as_deref().unwrap_or("*"),
+            self.sample_rate
+        )
+    }
+}
+
+/// Progress of an interrupted run. The cursor is only advanced after every
+/// key of the batch it produced has been compared, so resuming never skips keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub scope: Scope,
+    pub pass: Pass,
+    pub cursor: u64,
+    pub report: Report,
+}
+
+impl Checkpoint {
+    /// Load the checkpoint at `path`, refusing one taken for another `scope`.
+    pub fn load(path: &Path, scope: &Scope) -> io::Result<Option<Self>> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let checkpoint: Self = serde_json::from_slice(&bytes).map_err(io::Error::other)?;
+        if checkpoint.scope != *scope {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} was written for {}, not {scope}; remove it to start over", path.display(), checkpoint.scope),
+            ));
+        }
+        Ok(Some(checkpoint))
+    }
+
+    /// Write the checkpoint through a temporary file so a crash mid-write
+    /// leaves the previous checkpoint intact.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(self).map_err(io::Error::other)?)?;
+        fs::rename(tmp, path)
+    }
+
+    pub fn remove(path: &Path) -> io::Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_only_the_same_scope() {
+        let path = std::env::temp_dir().join(format!("eden-verify-checkpoint-{}.json", std::process::id()));
+        let scope = Scope {
+            source: "10.0.0.5:6379".to_string(),
+            dest: "10.0.0.6:6379".to_string(),
+            pattern: Some("user:*".to_string()),
+            sample_rate: 0.1,
+        };
+        let checkpoint = Checkpoint {
+            scope: scope.clone(),
+            pass: Pass::Source,
+            cursor: 42,
+            report: Report::default(),
+        };
+        checkpoint.save(&path).expect("save");
+
+        let resumed = Checkpoint::load(&path, &scope).expect("same scope").expect("checkpoint exists");
+        assert_eq!(resumed.cursor, 42);
+        let other = Scope { pattern: None, ..scope };
+        let err = Checkpoint::load(&path, &other).expect_err("different pattern");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        Checkpoint::remove(&path).expect("remove");
+        assert!(Checkpoint::load(&path, &other).expect("no checkpoint").is_none());
+    }
+}
@@ -0,0 +1,10 @@
+pub mod checkpoint;
+pub mod mongo;
+pub mod mysql;
+pub mod postgres;
+pub mod report;
+pub mod snapshot;
+pub mod sql;
+pub mod table;
+pub mod verify;
+pub mod watch;
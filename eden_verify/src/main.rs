@@ -0,0 +1,357 @@
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+use clap::Parser;
+use eden_client::audit::redact_url;
+
+use eden_verify::mongo::{self, CollectionConfig, is_mongo_url};
+use eden_verify::report::Report;
+use eden_verify::snapshot::{self, RollbackConfig, SnapshotConfig};
+use eden_verify::sql::{SqlTables, is_sql_url};
+use eden_verify::table::{self, TableConfig};
+use eden_verify::verify::{self, VerifyConfig, VerifyError};
+use eden_verify::watch::{self, WatchConfig};
+
+/// Exit code used when verification completes but finds mismatches.
+const MISMATCH_EXIT_CODE: i32 = 2;
+
+/// Verify that a destination Redis, Postgres, MySQL or MongoDB holds the same
+/// dataset as the source. Databases are selected by `postgres://`, `mysql://`
+/// and `mongodb://` URLs.
+#[derive(Parser)]
+#[command(name = "eden-verify")]
+struct Cli {
+    /// Source Redis (host:port or redis:// URL), Postgres (postgres:// URL) or
+    /// MySQL (mysql:// URL) or MongoDB (mongodb:// URL).
+    #[arg(long)]
+    source: String,
+
+    /// Destination, in the same form as the source.
+    #[arg(long, required_unless_present = "snapshot")]
+    dest: Option<String>,
+
+    /// Only verify keys matching this SCAN MATCH pattern.
+    #[arg(long = "match")]
+    pattern: Option<String>,
+
+    /// SCAN COUNT hint and number of keys compared between checkpoints.
+    #[arg(long, default_value_t = 1000)]
+    batch_size: usize,
+
+    /// Concurrent comparison pipelines per batch.
+    #[arg(long, default_value_t = 8)]
+    workers: usize,
+
+    /// Fraction of keys to verify, in (0, 1]. Sampling is deterministic per key.
+    #[arg(long, default_value_t = 1.0)]
+    sample_rate: f64,
+
+    /// Allowed TTL difference between source and destination, in seconds.
+    #[arg(long, default_value_t = 5)]
+    ttl_tolerance_secs: u64,
+
+    /// Allowed absolute difference between sorted-set scores.
+    #[arg(long, default_value_t = 1e-9)]
+    score_epsilon: f64,
+
+    /// Also scan the destination and report keys (or tables) missing from the source.
+    #[arg(long)]
+    bidirectional: bool,
+
+    /// Checkpoint file; an existing checkpoint resumes the interrupted run.
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+
+    /// Maximum number of individual mismatches listed in the report.
+    #[arg(long, default_value_t = 100)]
+    max_reported: usize,
+
+    /// Schema whose tables are compared, or MongoDB database. Defaults to
+    /// `public` on Postgres and to the URL's database on MySQL and MongoDB.
+    #[arg(long)]
+    schema: Option<String>,
+
+    /// Destination schema, when it differs from --schema.
+    #[arg(long)]
+    dest_schema: Option<String>,
+
+    /// Primary-key range covered by each MySQL checksum query.
+    #[arg(long, default_value_t = 10_000)]
+    chunk_size: u64,
+
+    /// Comma-separated tables or collections to compare; defaults to all.
+    #[arg(long, value_delimiter = ',')]
+    tables: Vec<String>,
+
+    /// PEM bundle of CAs trusted for Postgres TLS in addition to the web PKI
+    /// roots, e.g. the RDS global bundle.
+    #[arg(long)]
+    ca_cert: Option<PathBuf>,
+
+    /// Compare table content checksums as well as row counts.
+    #[arg(long)]
+    checksum: bool,
+
+    /// MongoDB documents sampled per collection; 0 compares counts only.
+    #[arg(long, default_value_t = 1000)]
+    sample_docs: usize,
+
+    /// Write a snapshot of the Redis source to this file and exit. Take one
+    /// before a migration to check a later rollback with --verify-rollback.
+    #[arg(long, conflicts_with = "verify_rollback")]
+    snapshot: Option<PathBuf>,
+
+    /// Check a rolled-back Redis source against this snapshot, and the
+    /// destination for keys the source no longer has.
+    #[arg(long)]
+    verify_rollback: Option<PathBuf>,
+
+    /// Sample Redis keys as they are written on the source and compare them
+    /// with the destination, reporting a running consistency percentage.
+    /// Runs until Ctrl-C or --watch-secs.
+    #[arg(long, conflicts_with_all = ["snapshot", "verify_rollback", "checkpoint", "bidirectional"])]
+    watch: bool,
+
+    /// Stop watching after this many seconds.
+    #[arg(long, requires = "watch")]
+    watch_secs: Option<u64>,
+
+    /// Milliseconds a write may take to reach the destination before the key
+    /// is compared.
+    #[arg(long, default_value_t = 1000)]
+    staleness_ms: u64,
+
+    /// Seconds between consistency lines while watching.
+    #[arg(long, default_value_t = 5)]
+    report_interval_secs: u64,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    if cli.snapshot.is_some() {
+        match take_snapshot(cli).await {
+            Ok(summary) => {
+                eprintln!(
+                    "done: scanned={} keys={} vanished={} elapsed={:.1}s",
+                    summary.scanned, summary.keys, summary.vanished, summary.elapsed_secs
+                );
+                let json = serde_json::to_string_pretty(&summary).expect("JSON serialization");
+                println!("{json}");
+            }
+            Err(e) => {
+                eprintln!("error: {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let result = if cli.watch {
+        watch_keys(cli).await
+    } else if cli.verify_rollback.is_some() {
+        verify_rollback(cli).await
+    } else if is_mongo_url(&cli.source) || is_mongo_url(&dest(&cli)) {
+        verify_collections(cli).await
+    } else if is_sql_url(&cli.source) || is_sql_url(&dest(&cli)) {
+        verify_tables(cli).await
+    } else {
+        verify_keys(cli).await
+    };
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    };
+
+    print_summary(&report);
+    let json = serde_json::to_string_pretty(&report).expect("JSON serialization");
+    println!("{json}");
+
+    if !report.passed {
+        process::exit(MISMATCH_EXIT_CODE);
+    }
+}
+
+async fn take_snapshot(cli: Cli) -> Result<snapshot::SnapshotSummary, VerifyError> {
+    if is_sql_url(&cli.source) || is_mongo_url(&cli.source) {
+        return Err(VerifyError::Config("--snapshot supports Redis sources only".to_string()));
+    }
+    let config = SnapshotConfig {
+        source: cli.source,
+        output: cli.snapshot.expect("--snapshot is set"),
+        pattern: cli.pattern,
+        batch_size: cli.batch_size,
+        sample_rate: cli.sample_rate,
+    };
+
+    eprintln!("eden-verify: snapshot of {} to {}", redact_url(&config.source), config.output.display());
+    snapshot::capture(&config).await
+}
+
+async fn verify_rollback(cli: Cli) -> Result<Report, VerifyError> {
+    let dest = dest(&cli);
+    if [&cli.source, &dest].iter().any(|url| is_sql_url(url) || is_mongo_url(url)) {
+        return Err(VerifyError::Config("--verify-rollback supports Redis only".to_string()));
+    }
+    let config = RollbackConfig {
+        source: cli.source,
+        dest,
+        snapshot: cli.verify_rollback.expect("--verify-rollback is set"),
+        pattern: cli.pattern,
+        batch_size: cli.batch_size,
+        max_reported: cli.max_reported,
+    };
+
+    eprintln!(
+        "eden-verify: rollback of {} against snapshot {}, dest={}",
+        redact_url(&config.source),
+        config.snapshot.display(),
+        redact_url(&config.dest)
+    );
+    snapshot::verify_rollback(&config).await
+}
+
+async fn watch_keys(cli: Cli) -> Result<Report, VerifyError> {
+    let dest = dest(&cli);
+    if [&cli.source, &dest].iter().any(|url| is_sql_url(url) || is_mongo_url(url)) {
+        return Err(VerifyError::Config("--watch supports Redis only".to_string()));
+    }
+    let watch_secs = cli.watch_secs;
+    let config = WatchConfig {
+        staleness: Duration::from_millis(cli.staleness_ms),
+        report_interval: Duration::from_secs(cli.report_interval_secs),
+        compare: key_config(cli, dest),
+    };
+
+    eprintln!(
+        "eden-verify: watching writes on {} against {} staleness={}ms sample_rate={}; Ctrl-C to stop",
+        redact_url(&config.compare.source),
+        redact_url(&config.compare.dest),
+        config.staleness.as_millis(),
+        config.compare.sample_rate
+    );
+    watch::run(&config, async {
+        match watch_secs {
+            Some(secs) => tokio::time::sleep(Duration::from_secs(secs)).await,
+            None => {
+                if let Err(e) = tokio::signal::ctrl_c().await {
+                    eprintln!("eden-verify: cannot listen for Ctrl-C: {e}");
+                    std::future::pending::<()>().await;
+                }
+            }
+        }
+    })
+    .await
+}
+
+async fn verify_keys(cli: Cli) -> Result<Report, VerifyError> {
+    let dest = dest(&cli);
+    let config = key_config(cli, dest);
+
+    eprintln!(
+        "eden-verify: source={} dest={} sample_rate={} workers={}",
+        redact_url(&config.source),
+        redact_url(&config.dest),
+        config.sample_rate,
+        config.workers
+    );
+    verify::run(&config).await
+}
+
+fn key_config(cli: Cli, dest: String) -> VerifyConfig {
+    VerifyConfig {
+        source: cli.source,
+        dest,
+        pattern: cli.pattern,
+        batch_size: cli.batch_size,
+        workers: cli.workers,
+        sample_rate: cli.sample_rate,
+        ttl_tolerance: Duration::from_secs(cli.ttl_tolerance_secs),
+        score_epsilon: cli.score_epsilon,
+        bidirectional: cli.bidirectional,
+        checkpoint: cli.checkpoint,
+        max_reported: cli.max_reported,
+    }
+}
+
+async fn verify_tables(cli: Cli) -> Result<Report, VerifyError> {
+    let dest = dest(&cli);
+    if !is_sql_url(&cli.source) || !is_sql_url(&dest) {
+        return Err(VerifyError::Config("source and dest must both be postgres:// or mysql:// URLs".to_string()));
+    }
+    let source_schema = cli.schema.as_deref();
+    let dest_schema = cli.dest_schema.as_deref().or(source_schema);
+    let config = TableConfig {
+        source: cli.source,
+        dest,
+        tables: cli.tables,
+        checksum: cli.checksum,
+        bidirectional: cli.bidirectional,
+        max_reported: cli.max_reported,
+    };
+
+    eprintln!(
+        "eden-verify: source={} dest={} checksum={}",
+        redact_url(&config.source),
+        redact_url(&config.dest),
+        config.checksum
+    );
+    let ca_cert = cli.ca_cert.as_deref();
+    let mut source = SqlTables::connect(&config.source, source_schema, cli.chunk_size, ca_cert).await?;
+    let mut dest = SqlTables::connect(&config.dest, dest_schema, cli.chunk_size, ca_cert).await?;
+    table::compare(&mut source, &mut dest, &config).await
+}
+
+async fn verify_collections(cli: Cli) -> Result<Report, VerifyError> {
+    let dest = dest(&cli);
+    if !is_mongo_url(&cli.source) || !is_mongo_url(&dest) {
+        return Err(VerifyError::Config("source and dest must both be mongodb:// URLs".to_string()));
+    }
+    let config = CollectionConfig {
+        source: cli.source,
+        dest,
+        dest_database: cli.dest_schema.or(cli.schema.clone()),
+        source_database: cli.schema,
+        collections: cli.tables,
+        sample_size: cli.sample_docs,
+        bidirectional: cli.bidirectional,
+        max_reported: cli.max_reported,
+    };
+
+    eprintln!(
+        "eden-verify: source={} dest={} sample_docs={}",
+        redact_url(&config.source),
+        redact_url(&config.dest),
+        config.sample_size
+    );
+    mongo::compare(&config).await
+}
+
+/// Every mode except --snapshot requires --dest.
+fn dest(cli: &Cli) -> String {
+    cli.dest.clone().expect("clap requires --dest without --snapshot")
+}
+
+fn print_summary(report: &Report) {
+    let counts = &report.counts;
+    eprintln!(
+        "done: scanned={} checked={} matched={} missing={} unexpected={} type={} value={} ttl={} errors={} vanished={} elapsed={:.1}s",
+        counts.scanned,
+        counts.checked,
+        counts.matched,
+        counts.missing,
+        counts.unexpected,
+        counts.type_mismatch,
+        counts.value_mismatch,
+        counts.ttl_mismatch,
+        counts.errors,
+        counts.vanished,
+        report.elapsed_secs,
+    );
+    eprintln!("result: {}", if report.passed { "PASS" } else { "FAIL" });
+}
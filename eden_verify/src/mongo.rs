@@ -0,0 +1,209 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
+
+use eden_client::audit::redact_url;
+use futures::TryStreamExt;
+use mongodb::bson::{Bson, Document, doc};
+use mongodb::{Client, Database};
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::report::{Mismatch, MismatchKind, Report};
+use crate::verify::VerifyError;
+
+pub fn is_mongo_url(url: &str) -> bool {
+    url.starts_with("mongodb://") || url.starts_with("mongodb+srv://")
+}
+
+#[derive(Debug, Clone)]
+pub struct CollectionConfig {
+    pub source: String,
+    pub dest: String,
+    /// Databases to compare; each defaults to the database named in its URL.
+    pub source_database: Option<String>,
+    pub dest_database: Option<String>,
+    /// Collections to compare; empty compares every collection.
+    pub collections: Vec<String>,
+    /// Source documents sampled per collection for `_id` overlap and content
+    /// hashing. Zero compares document counts only.
+    pub sample_size: usize,
+    /// Also report collections that exist only on the destination.
+    pub bidirectional: bool,
+    pub max_reported: usize,
+}
+
+/// Compare the collections of two MongoDB databases by document count, then
+/// check that a random sample of source documents exists on the destination
+/// with the same content. Mismatches are reported per collection, with the
+/// collection name in the `key` field of the report.
+pub async fn compare(config: &CollectionConfig) -> Result<Report, VerifyError> {
+    let started = Instant::now();
+    let source = connect(&config.source, config.source_database.as_deref()).await?;
+    let dest = connect(&config.dest, config.dest_database.as_deref()).await?;
+    let source_collections = collection_names(&source).await?;
+    let dest_collections = collection_names(&dest).await?;
+
+    let mut report = Report {
+        source: redact_url(&config.source),
+        dest: redact_url(&config.dest),
+        sample_rate: 1.0,
+        ..Report::default()
+    };
+    report.counts.scanned = source_collections.len() as u64;
+
+    let selected: Vec<&String> = if config.collections.is_empty() {
+        source_collections.iter().collect()
+    } else {
+        config.collections.iter().collect()
+    };
+
+    for name in selected {
+        report.counts.checked += 1;
+        if !source_collections.contains(name) {
+            report.record(mismatch(name, MismatchKind::Error, "collection not found on source"), config.max_reported);
+            continue;
+        }
+        if !dest_collections.contains(name) {
+            report.record(mismatch(name, MismatchKind::Missing, "collection missing from dest"), config.max_reported);
+            continue;
+        }
+        match compare_collection(&source, &dest, name, config.sample_size).await {
+            Ok(None) => report.counts.matched += 1,
+            Ok(Some((kind, detail))) => report.record(mismatch(name, kind, &detail), config.max_reported),
+            Err(e) => report.record(mismatch(name, MismatchKind::Error, &e.to_string()), config.max_reported),
+        }
+    }
+
+    if config.bidirectional {
+        let unexpected = dest_collections.difference(&source_collections);
+        for name in unexpected.filter(|name| config.collections.is_empty() || config.collections.contains(name)) {
+            report.record(mismatch(name, MismatchKind::Unexpected, "collection not present on source"), config.max_reported);
+        }
+    }
+
+    report.elapsed_secs = started.elapsed().as_secs_f64();
+    report.passed = report.counts.mismatches() == 0;
+    Ok(report)
+}
+
+async fn connect(url: &str, database: Option<&str>) -> Result<Database, VerifyError> {
+    let client = Client::with_uri_str(url).await?;
+    match database {
+        Some(name) => Ok(client.database(name)),
+        None => client
+            .default_database()
+            .ok_or_else(|| VerifyError::Config(format!("{} names no database; pass --schema", redact_url(url)))),
+    }
+}
+
+/// User collections, skipping `system.*` collections and views.
+async fn collection_names(database: &Database) -> Result<BTreeSet<String>, VerifyError> {
+    let names = database.list_collection_names(doc! { "type": "collection" }).await?;
+    Ok(names.into_iter().filter(|name| !name.starts_with("system.")).collect())
+}
+
+async fn compare_collection(
+    source: &Database,
+    dest: &Database,
+    name: &str,
+    sample_size: usize,
+) -> Result<Option<(MismatchKind, String)>, VerifyError> {
+    let source = source.collection::<Document>(name);
+    let dest = dest.collection::<Document>(name);
+
+    let (source_count, dest_count) = tokio::try_join!(source.count_documents(None, None), dest.count_documents(None, None))?;
+    if source_count != dest_count {
+        return Ok(Some((MismatchKind::ValueMismatch, format!("document count {source_count} vs {dest_count}"))));
+    }
+    if sample_size == 0 {
+        return Ok(None);
+    }
+
+    let pipeline = [doc! { "$sample": { "size": sample_size as i64 } }];
+    let sample: Vec<Document> = source.aggregate(pipeline, None).await?.try_collect().await?;
+    let ids: Vec<Bson> = sample.iter().filter_map(|document| document.get("_id").cloned()).collect();
+    let found: Vec<Document> = dest.find(doc! { "_id": { "$in": ids } }, None).await?.try_collect().await?;
+    Ok(describe_sample_difference(&sample, &found))
+}
+
+/// Match sampled source documents to destination documents by `_id`,
+/// reporting missing ids before content differences.
+pub fn describe_sample_difference(sample: &[Document], found: &[Document]) -> Option<(MismatchKind, String)> {
+    let found: HashMap<String, u64> = found.iter().map(|document| (id_key(document), document_hash(document))).collect();
+    let mut missing = Vec::new();
+    let mut differing = Vec::new();
+    for document in sample {
+        let id = id_key(document);
+        match found.get(&id) {
+            None => missing.push(id),
+            Some(hash) if *hash != document_hash(document) => differing.push(id),
+            Some(_) => {}
+        }
+    }
+    if let Some(first) = missing.first() {
+        let detail = format!("{} of {} sampled _ids missing from dest, e.g. {first}", missing.len(), sample.len());
+        return Some((MismatchKind::Missing, detail));
+    }
+    let first = differing.first()?;
+    let detail = format!("{} of {} sampled documents differ, e.g. _id {first}", differing.len(), sample.len());
+    Some((MismatchKind::ValueMismatch, detail))
+}
+
+fn id_key(document: &Document) -> String {
+    document.get("_id").map(Bson::to_string).unwrap_or_default()
+}
+
+/// Hash of a document with its fields sorted at every level, so documents
+/// rewritten with a different field order still match.
+pub fn document_hash(document: &Document) -> u64 {
+    let canonical = mongodb::bson::to_vec(&sort_fields(document)).expect("BSON document serializes");
+    xxh3_64(&canonical)
+}
+
+fn sort_fields(document: &Document) -> Document {
+    let mut fields: Vec<(&String, &Bson)> = document.iter().collect();
+    fields.sort_by(|a, b| a.0.cmp(b.0));
+    fields.into_iter().map(|(key, value)| (key.clone(), sort_value(value))).collect()
+}
+
+fn sort_value(value: &Bson) -> Bson {
+    match value {
+        Bson::Document(document) => Bson::Document(sort_fields(document)),
+        Bson::Array(values) => Bson::Array(values.iter().map(sort_value).collect()),
+        other => other.clone(),
+    }
+}
+
+fn mismatch(collection: &str, kind: MismatchKind, detail: &str) -> Mismatch {
+    Mismatch {
+        key: collection.to_string(),
+        kind,
+        detail: detail.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_hash_ignores_field_order() {
+        let a = doc! { "_id": 1, "name": "ada", "address": { "city": "London", "zip": "N1" } };
+        let b = doc! { "address": { "zip": "N1", "city": "London" }, "name": "ada", "_id": 1 };
+        assert_eq!(document_hash(&a), document_hash(&b));
+        assert_ne!(document_hash(&a), document_hash(&doc! { "_id": 1, "name": "bob" }));
+    }
+
+    #[test]
+    fn sample_difference_reports_missing_before_changed() {
+        let sample = [doc! { "_id": 1, "v": "a" }, doc! { "_id": 2, "v": "b" }];
+        assert_eq!(describe_sample_difference(&sample, &sample), None);
+        assert_eq!(
+            describe_sample_difference(&sample, &[doc! { "_id": 1, "v": "changed" }]),
+            Some((MismatchKind::Missing, "1 of 2 sampled _ids missing from dest, e.g. 2".to_string()))
+        );
+        assert_eq!(
+            describe_sample_difference(&sample, &[doc! { "_id": 1, "v": "a" }, doc! { "_id": 2, "v": "changed" }]),
+            Some((MismatchKind::ValueMismatch, "1 of 2 sampled documents differ, e.g. _id 2".to_string()))
+        );
+    }
+}
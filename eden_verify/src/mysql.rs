@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+
+use mysql_async::Conn;
+use mysql_async::prelude::Queryable;
+
+use crate::table::{ChunkStats, Column, TableSource, TableStats};
+use crate::verify::VerifyError;
+
+const COLUMNS_QUERY: &str = "\
+    SELECT c.TABLE_NAME, c.COLUMN_NAME, c.COLUMN_TYPE, c.IS_NULLABLE = 'YES' \
+    FROM information_schema.COLUMNS c \
+    JOIN information_schema.TABLES t ON t.TABLE_SCHEMA = c.TABLE_SCHEMA AND t.TABLE_NAME = c.TABLE_NAME \
+    WHERE c.TABLE_SCHEMA = ? AND t.TABLE_TYPE = 'BASE TABLE' \
+    ORDER BY c.TABLE_NAME, c.ORDINAL_POSITION";
+
+const PRIMARY_KEY_QUERY: &str = "\
+    SELECT COLUMN_NAME, DATA_TYPE FROM information_schema.COLUMNS \
+    WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND COLUMN_KEY = 'PRI'";
+
+const INTEGER_TYPES: &[&str] = &["tinyint", "smallint", "mediumint", "int", "bigint"];
+
+pub fn is_mysql_url(url: &str) -> bool {
+    url.starts_with("mysql://")
+}
+
+/// The tables of one MySQL database.
+///
+/// Checksums follow pt-table-checksum: each row is hashed with `CRC32` over
+/// its columns joined by `CONCAT_WS`, plus a NULL bitmap, and the hashes are
+/// combined with `BIT_XOR`. Unlike `CHECKSUM TABLE`, the result does not
+/// depend on storage format or server version.
+pub struct MysqlTables {
+    conn: Conn,
+    schema: String,
+    chunk_size: i64,
+}
+
+impl MysqlTables {
+    /// Connect to `url`. `schema` defaults to the database named in the URL.
+    pub async fn connect(url: &str, schema: Option<&str>, chunk_size: u64) -> Result<Self, VerifyError> {
+        let mut conn = Conn::from_url(url).await?;
+        let schema = match schema {
+            Some(schema) => schema.to_string(),
+            None => conn
+                .query_first::<Option<String>, _>("SELECT DATABASE()")
+                .await?
+                .flatten()
+                .ok_or_else(|| VerifyError::Config("mysql URL names no database; pass --schema".to_string()))?,
+        };
+        let chunk_size = i64::try_from(chunk_size).ok().filter(|size| *size > 0);
+        let chunk_size = chunk_size.ok_or_else(|| VerifyError::Config("chunk size must be > 0".to_string()))?;
+        Ok(Self { conn, schema, chunk_size })
+    }
+
+    /// The primary key column, if the table has a single integer one.
+    async fn integer_primary_key(&mut self, table: &str) -> Result<Option<String>, VerifyError> {
+        let keys: Vec<(String, String)> = self.conn.exec(PRIMARY_KEY_QUERY, (&self.schema, table)).await?;
+        let [(name, data_type)] = keys.as_slice() else {
+            return Ok(None);
+        };
+        Ok(INTEGER_TYPES.contains(&data_type.to_lowercase().as_str()).then(|| name.clone()))
+    }
+
+    /// Checksum the table in primary-key ranges aligned to the chunk size.
+    /// Empty ranges are skipped by jumping to the next existing key.
+    async fn chunked_stats(&mut self, relation: &str, key: &str, hash: &str) -> Result<TableStats, VerifyError> {
+        let chunk_query = format!("{} WHERE {key} >= ? AND {key} < ?", checksum_query(relation, hash));
+        let next_query = format!("SELECT MIN({key}) FROM {relation} WHERE {key} >= ?");
+
+        let mut stats = TableStats::default();
+        let mut checksum = 0u64;
+        let mut next = self.conn.query_first::<Option<i64>, _>(format!("SELECT MIN({key}) FROM {relation}")).await?.flatten();
+        while let Some(lowest) = next {
+            let start = lowest.div_euclid(self.chunk_size) * self.chunk_size;
+            let end = start.saturating_add(self.chunk_size);
+            let (rows, chunk_checksum): (u64, u64) = self.conn.exec_first(&chunk_query, (start, end)).await?.unwrap_or_default();
+            stats.rows += rows;
+            checksum ^= chunk_checksum;
+            stats.chunks.push(ChunkStats { start, end, rows, checksum: chunk_checksum });
+
+            next = match end {
+                i64::MAX => None,
+                end => self.conn.exec_first::<Option<i64>, _, _>(&next_query, (end,)).await?.flatten(),
+            };
+        }
+        stats.checksum = Some(format!("{checksum:016x}"));
+        Ok(stats)
+    }
+}
+
+impl TableSource for MysqlTables {
+    fn engine(&self) -> &'static str {
+        "mysql"
+    }
+
+    async fn tables(&mut self) -> Result<BTreeMap<String, Vec<Column>>, VerifyError> {
+        let rows: Vec<(String, String, String, i64)> = self.conn.exec(COLUMNS_QUERY, (&self.schema,)).await?;
+        let mut tables: BTreeMap<String, Vec<Column>> = BTreeMap::new();
+        for (table, name, data_type, nullable) in rows {
+            tables.entry(table).or_default().push(Column { name, data_type, nullable: nullable != 0 });
+        }
+        Ok(tables)
+    }
+
+    async fn stats(&mut self, table: &str, columns: &[Column], checksum: bool) -> Result<TableStats, VerifyError> {
+        let relation = format!("{}.{}", quote_ident(&self.schema), quote_ident(table));
+        if !checksum {
+            let rows: u64 = self.conn.query_first(format!("SELECT COUNT(*) FROM {relation}")).await?.unwrap_or_default();
+            return Ok(TableStats { rows, ..TableStats::default() });
+        }
+
+        let hash = row_hash(columns);
+        if let Some(key) = self.integer_primary_key(table).await? {
+            return self.chunked_stats(&relation, &quote_ident(&key), &hash).await;
+        }
+        let (rows, checksum): (u64, u64) = self.conn.query_first(checksum_query(&relation, &hash)).await?.unwrap_or_default();
+        Ok(TableStats {
+            rows,
+            checksum: Some(format!("{checksum:016x}")),
+            chunks: Vec::new(),
+        })
+    }
+}
+
+fn checksum_query(relation: &str, hash: &str) -> String {
+    format!("SELECT COUNT(*), COALESCE(BIT_XOR(CAST({hash} AS UNSIGNED)), 0) FROM {relation}")
+}
+
+/// `CONCAT_WS` skips NULLs, so a NULL bitmap is appended to tell `(NULL, 'a')`
+/// and `('a', NULL)` apart. Columns are hashed in name order, ignoring case
+/// like MySQL does, so a different column order does not change the checksum.
+fn row_hash(columns: &[Column]) -> String {
+    let mut names: Vec<&str> = columns.iter().map(|column| column.name.as_str()).collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    let quoted: Vec<String> = names.iter().map(|name| quote_ident(name)).collect();
+    let nulls: Vec<String> = quoted.iter().map(|column| format!("ISNULL({column})")).collect();
+    format!("CRC32(CONCAT_WS('#', {}, CONCAT({})))", quoted.join(", "), nulls.join(", "))
+}
+
+pub fn quote_ident(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_hash_covers_every_column_and_null_bitmap() {
+        let columns = [
+            Column {
+                name: "odd`name".to_string(),
+                data_type: "text".to_string(),
+                nullable: true,
+            },
+            Column {
+                name: "ID".to_string(),
+                data_type: "int".to_string(),
+                nullable: false,
+            },
+        ];
+        assert_eq!(
+            row_hash(&columns),
+            "CRC32(CONCAT_WS('#', `ID`, `odd``name`, CONCAT(ISNULL(`ID`), ISNULL(`odd``name`))))"
+        );
+    }
+}
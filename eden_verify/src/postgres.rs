@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use rustls::{ClientConfig, RootCertStore};
+use tokio_postgres::Client;
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+use crate::table::{Column, TableSource, TableStats};
+use crate::verify::VerifyError;
+
+/// Column listing for every base table of a schema. Identifier columns are
+/// cast to text because `information_schema` declares them as domains.
+const COLUMNS_QUERY: &str = "\
+    SELECT c.table_name::text, c.column_name::text, c.data_type::text, c.is_nullable::text = 'YES' \
+    FROM information_schema.columns c \
+    JOIN information_schema.tables t ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+    WHERE c.table_schema = $1 AND t.table_type = 'BASE TABLE' \
+    ORDER BY c.table_name, c.ordinal_position";
+
+pub fn is_postgres_url(url: &str) -> bool {
+    url.starts_with("postgres://") || url.starts_with("postgresql://")
+}
+
+/// The tables of one Postgres schema.
+pub struct PostgresTables {
+    client: Client,
+    schema: String,
+}
+
+impl PostgresTables {
+    /// Connect over TLS when the server offers it, or must, with the URL's
+    /// `sslmode=require`. Server certificates are checked against the web PKI
+    /// roots and `ca_cert`; `sslmode=disable` connects in plain text.
+    pub async fn connect(url: &str, schema: &str, ca_cert: Option<&Path>) -> Result<Self, VerifyError> {
+        let tls = MakeRustlsConnect::new(tls_config(ca_cert)?);
+        let (client, connection) = tokio_postgres::connect(url, tls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                eprintln!("eden-verify: postgres connection error: {e}");
+            }
+        });
+        Ok(Self { client, schema: schema.to_string() })
+    }
+}
+
+fn tls_config(ca_cert: Option<&Path>) -> Result<ClientConfig, VerifyError> {
+    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+    let mut roots = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    if let Some(path) = ca_cert {
+        let invalid = |e: &dyn std::fmt::Display| VerifyError::Config(format!("{}: {e}", path.display()));
+        let pem = fs::read(path).map_err(|e| invalid(&e))?;
+        let certs: Vec<_> = rustls_pemfile::certs(&mut pem.as_slice()).collect::<Result<_, _>>().map_err(|e| invalid(&e))?;
+        if certs.is_empty() {
+            return Err(invalid(&"no certificates found"));
+        }
+        for cert in certs {
+            roots.add(cert).map_err(|e| invalid(&e))?;
+        }
+    }
+    Ok(ClientConfig::builder().with_root_certificates(roots).with_no_client_auth())
+}
+
+impl TableSource for PostgresTables {
+    fn engine(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn tables(&mut self) -> Result<BTreeMap<String, Vec<Column>>, VerifyError> {
+        let mut tables: BTreeMap<String, Vec<Column>> = BTreeMap::new();
+        for row in self.client.query(COLUMNS_QUERY, &[&self.schema]).await? {
+            tables.entry(row.get(0)).or_default().push(Column {
+                name: row.get(1),
+                data_type: row.get(2),
+                nullable: row.get(3),
+            });
+        }
+        Ok(tables)
+    }
+
+    /// The checksum sums the first 64 bits of each row's md5, which is
+    /// independent of row order and needs no sort or temporary storage.
+    async fn stats(&mut self, table: &str, columns: &[Column], checksum: bool) -> Result<TableStats, VerifyError> {
+        let relation = format!("{}.{}", quote_ident(&self.schema), quote_ident(table));
+        if !checksum {
+            let row = self.client.query_one(&format!("SELECT count(*) FROM {relation}"), &[]).await?;
+            return Ok(TableStats { rows: row.get::<_, i64>(0) as u64, ..TableStats::default() });
+        }
+        let query = format!(
+            "SELECT count(*), coalesce(sum(('x' || substr(md5({}), 1, 16))::bit(64)::bigint::numeric), 0)::text \
+             FROM {relation} AS t",
+            row_text(columns)
+        );
+        let row = self.client.query_one(&query, &[]).await?;
+        Ok(TableStats {
+            rows: row.get::<_, i64>(0) as u64,
+            checksum: Some(row.get(1)),
+            chunks: Vec::new(),
+        })
+    }
+}
+
+/// Columns are listed by name rather than with `t::text`, so tables whose
+/// columns were created in a different order still checksum the same.
+fn row_text(columns: &[Column]) -> String {
+    let mut names: Vec<&str> = columns.iter().map(|column| column.name.as_str()).collect();
+    names.sort_unstable();
+    let fields: Vec<String> = names.iter().map(|name| format!("t.{}", quote_ident(name))).collect();
+    format!("ROW({})::text", fields.join(", "))
+}
+
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tls_config_rejects_unreadable_ca_bundles() {
+        assert!(tls_config(None).is_ok());
+        let err = tls_config(Some(Path::new("/nonexistent/ca.pem"))).expect_err("missing CA file");
+        assert!(err.to_string().contains("/nonexistent/ca.pem"), "{err}");
+    }
+
+    #[test]
+    fn row_text_lists_columns_by_name() {
+        let column = |name: &str| Column {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            nullable: true,
+        };
+        assert_eq!(
+            row_text(&[column("name"), column("id"), column("a\"b")]),
+            "ROW(t.\"a\"\"b\", t.\"id\", t.\"name\")::text"
+        );
+    }
+
+    #[test]
+    fn quote_ident_escapes_quotes() {
+        assert_eq!(quote_ident("orders"), "\"orders\"");
+        assert_eq!(quote_ident("odd\"name"), "\"odd\"\"name\"");
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// Present on the source, absent on the destination.
+    Missing,
+    /// Present on the destination, absent on the source (bidirectional mode).
+    Unexpected,
+    TypeMismatch,
+    ValueMismatch,
+    TtlMismatch,
+    /// The key could not be read or decoded on one of the sides.
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mismatch {
+    pub key: String,
+    pub kind: MismatchKind,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Counts {
+    /// Keys returned by SCAN on the source.
+    pub scanned: u64,
+    /// Scanned keys selected for comparison.
+    pub checked: u64,
+    pub matched: u64,
+    pub missing: u64,
+    pub unexpected: u64,
+    pub type_mismatch: u64,
+    pub value_mismatch: u64,
+    pub ttl_mismatch: u64,
+    pub errors: u64,
+    /// Keys that expired or were deleted on the source between SCAN and read.
+    pub vanished: u64,
+}
+
+impl Counts {
+    pub fn mismatches(&self) -> u64 {
+        self.missing + self.unexpected + self.type_mismatch + self.value_mismatch + self.ttl_mismatch + self.errors
+    }
+
+    pub fn merge(&mut self, other: &Counts) {
+        self.scanned += other.scanned;
+        self.checked += other.checked;
+        self.matched += other.matched;
+        self.missing += other.missing;
+        self.unexpected += other.unexpected;
+        self.type_mismatch += other.type_mismatch;
+        self.value_mismatch += other.value_mismatch;
+        self.ttl_mismatch += other.ttl_mismatch;
+        self.errors += other.errors;
+        self.vanished += other.vanished;
+    }
+
+    fn bump(&mut self, kind: MismatchKind) {
+        match kind {
+            MismatchKind::Missing => self.missing += 1,
+            MismatchKind::Unexpected => self.unexpected += 1,
+            MismatchKind::TypeMismatch => self.type_mismatch += 1,
+            MismatchKind::ValueMismatch => self.value_mismatch += 1,
+            MismatchKind::TtlMismatch => self.ttl_mismatch += 1,
+            MismatchKind::Error => self.errors += 1,
+        }
+    }
+}
+
+/// Final verification result, also used as the checkpoint payload while a
+/// run is in progress.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Report {
+    pub source: String,
+    pub dest: String,
+    pub sample_rate: f64,
+    pub counts: Counts,
+    /// First mismatches found, capped at the configured report limit.
+    pub mismatches: Vec<Mismatch>,
+    pub elapsed_secs: f64,
+    pub passed: bool,
+}
+
+impl Report {
+    pub fn record(&mut self, mismatch: Mismatch, max_reported: usize) {
+        self.counts.bump(mismatch.kind);
+        if self.mismatches.len() < max_reported {
+            self.mismatches.push(mismatch);
+        }
+    }
+}
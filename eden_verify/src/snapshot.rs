@@ -0,0 +1,373 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use eden_client::audit::redact_url;
+use redis::RedisResult;
+use redis::aio::MultiplexedConnection;
+use redis_compare::digest::digest;
+use redis_compare::value::{fetch_values, lossy};
+use serde::{Deserialize, Serialize};
+
+use crate::report::{Mismatch, MismatchKind, Report};
+use crate::verify::{self, KeyOutcome, PROGRESS_INTERVAL, VerifyError, fetch_metadata, find_unexpected, is_sampled, scan_batch};
+
+/// State of one key when the snapshot was taken. Values are kept as digests,
+/// so a snapshot is small but can only tell that a value changed, not how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotEntry {
+    pub key: Vec<u8>,
+    /// Redis `TYPE` of the key.
+    pub type_name: String,
+    pub digest: u64,
+    /// Whether the key had a TTL.
+    pub expires: bool,
+}
+
+/// One JSON line of a snapshot file. Keys that are not UTF-8 are stored hex-encoded.
+#[derive(Serialize, Deserialize)]
+struct Line {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_hex: Option<String>,
+    #[serde(rename = "type")]
+    type_name: String,
+    digest: String,
+    expires: bool,
+}
+
+impl From<&SnapshotEntry> for Line {
+    fn from(entry: &SnapshotEntry) -> Self {
+        let (key, key_hex) = match std::str::from_utf8(&entry.key) {
+            Ok(key) => (Some(key.to_string()), None),
+            Err(_) => (None, Some(entry.key.iter().map(|b| format!("{b:02x}")).collect())),
+        };
+        Line {
+            key,
+            key_hex,
+            type_name: entry.type_name.clone(),
+            digest: format!("{:016x}", entry.digest),
+            expires: entry.expires,
+        }
+    }
+}
+
+impl TryFrom<Line> for SnapshotEntry {
+    type Error = io::Error;
+
+    fn try_from(line: Line) -> io::Result<Self> {
+        let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("invalid snapshot {what}"));
+        let key = match (line.key, line.key_hex) {
+            (Some(key), None) => key.into_bytes(),
+            (None, Some(hex)) if hex.len() % 2 == 0 => (0..hex.len())
+                .step_by(2)
+                .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+                .collect::<Option<_>>()
+                .ok_or_else(|| invalid("key_hex"))?,
+            _ => return Err(invalid("key")),
+        };
+        Ok(SnapshotEntry {
+            key,
+            type_name: line.type_name,
+            digest: u64::from_str_radix(&line.digest, 16).map_err(|_| invalid("digest"))?,
+            expires: line.expires,
+        })
+    }
+}
+
+/// Writes a snapshot as JSON lines, one key per line.
+pub struct SnapshotWriter {
+    out: BufWriter<File>,
+}
+
+impl SnapshotWriter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self { out: BufWriter::new(File::create(path)?) })
+    }
+
+    pub fn write(&mut self, entry: &SnapshotEntry) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, &Line::from(entry)).map_err(io::Error::other)?;
+        self.out.write_all(b"\n")
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Reads the entries of a snapshot file in the order they were written.
+pub struct SnapshotReader {
+    lines: Lines<BufReader<File>>,
+}
+
+impl SnapshotReader {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self { lines: BufReader::new(File::open(path)?).lines() })
+    }
+}
+
+impl Iterator for SnapshotReader {
+    type Item = io::Result<SnapshotEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.lines.next()? {
+            Ok(line) => line,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(
+            serde_json::from_str::<Line>(&line)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                .and_then(SnapshotEntry::try_from),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    pub source: String,
+    pub output: PathBuf,
+    /// Optional SCAN MATCH pattern restricting the snapshot.
+    pub pattern: Option<String>,
+    pub batch_size: usize,
+    /// Fraction of keys to record, in (0, 1], selected as in verification.
+    pub sample_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotSummary {
+    pub source: String,
+    /// Keys returned by SCAN.
+    pub scanned: u64,
+    /// Keys written to the snapshot.
+    pub keys: u64,
+    /// Sampled keys that expired or were deleted between SCAN and read.
+    pub vanished: u64,
+    pub elapsed_secs: f64,
+}
+
+/// Record the type, value digest and TTL presence of every sampled source key.
+pub async fn capture(config: &SnapshotConfig) -> Result<SnapshotSummary, VerifyError> {
+    if !(config.sample_rate > 0.0 && config.sample_rate <= 1.0) {
+        return Err(VerifyError::Config("sample rate must be in (0, 1]".to_string()));
+    }
+    if config.batch_size == 0 {
+        return Err(VerifyError::Config("batch size must be > 0".to_string()));
+    }
+
+    let started = Instant::now();
+    let mut conn = verify::connect(&config.source).await?;
+    let mut writer = SnapshotWriter::create(&config.output).map_err(VerifyError::Snapshot)?;
+    let mut summary = SnapshotSummary {
+        source: redact_url(&config.source),
+        scanned: 0,
+        keys: 0,
+        vanished: 0,
+        elapsed_secs: 0.0,
+    };
+    let mut last_progress = Instant::now();
+    let mut cursor = 0;
+    loop {
+        let (next_cursor, keys) = scan_batch(&mut conn, cursor, config.pattern.as_deref(), config.batch_size).await?;
+        summary.scanned += keys.len() as u64;
+        let selected: Vec<Vec<u8>> = keys.into_iter().filter(|key| is_sampled(key, config.sample_rate)).collect();
+        for entry in read_entries(&mut conn, selected).await? {
+            match entry {
+                Some(entry) => {
+                    writer.write(&entry).map_err(VerifyError::Snapshot)?;
+                    summary.keys += 1;
+                }
+                None => summary.vanished += 1,
+            }
+        }
+
+        cursor = next_cursor;
+        if cursor == 0 {
+            break;
+        }
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            eprintln!("  [{:.1}s] scanned={} keys={}", started.elapsed().as_secs_f64(), summary.scanned, summary.keys);
+        }
+    }
+    writer.finish().map_err(VerifyError::Snapshot)?;
+
+    summary.elapsed_secs = started.elapsed().as_secs_f64();
+    Ok(summary)
+}
+
+/// Current state of `keys`, with `None` for keys that no longer exist.
+async fn read_entries(conn: &mut MultiplexedConnection, keys: Vec<Vec<u8>>) -> RedisResult<Vec<Option<SnapshotEntry>>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let meta = fetch_metadata(conn, &keys).await?;
+    let types: Vec<String> = meta.iter().map(|(type_name, _)| type_name.clone()).collect();
+    let values = fetch_values(conn, &keys, &types).await?;
+
+    Ok(keys
+        .into_iter()
+        .zip(meta)
+        .zip(values)
+        .map(|((key, (type_name, pttl)), value)| match value {
+            Some(value) if type_name != "none" => Some(SnapshotEntry { key, type_name, digest: digest(&value), expires: pttl >= 0 }),
+            _ => None,
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone)]
+pub struct RollbackConfig {
+    /// The restored source.
+    pub source: String,
+    /// The destination that was rolled back from.
+    pub dest: String,
+    /// Snapshot taken from the source before the migration.
+    pub snapshot: PathBuf,
+    /// Optional SCAN MATCH pattern for the destination pass.
+    pub pattern: Option<String>,
+    pub batch_size: usize,
+    pub max_reported: usize,
+}
+
+/// Outcome of comparing one snapshot entry with the key's current state.
+#[derive(Debug, PartialEq)]
+enum Change {
+    Unchanged,
+    /// The key had a TTL and is gone, which is expected over time.
+    Expired,
+    Changed(MismatchKind, String),
+}
+
+fn compare_entry(before: &SnapshotEntry, after: Option<&SnapshotEntry>) -> Change {
+    let Some(after) = after else {
+        return if before.expires {
+            Change::Expired
+        } else {
+            Change::Changed(MismatchKind::Missing, format!("{} lost since snapshot", before.type_name))
+        };
+    };
+    if before.type_name != after.type_name {
+        Change::Changed(MismatchKind::TypeMismatch, format!("was {}, now {}", before.type_name, after.type_name))
+    } else if before.digest != after.digest {
+        Change::Changed(MismatchKind::ValueMismatch, format!("{} value changed since snapshot", after.type_name))
+    } else if before.expires != after.expires {
+        let detail = if after.expires {
+            "gained a TTL since snapshot"
+        } else {
+            "lost its TTL since snapshot"
+        };
+        Change::Changed(MismatchKind::TtlMismatch, detail.to_string())
+    } else {
+        Change::Unchanged
+    }
+}
+
+/// After a rollback, check that every snapshot key is back on the source
+/// unchanged and that the destination holds no keys the source lacks, which
+/// would be writes stranded by the rollback.
+pub async fn verify_rollback(config: &RollbackConfig) -> Result<Report, VerifyError> {
+    if config.batch_size == 0 {
+        return Err(VerifyError::Config("batch size must be > 0".to_string()));
+    }
+
+    let started = Instant::now();
+    let mut snapshot = SnapshotReader::open(&config.snapshot).map_err(VerifyError::Snapshot)?;
+    let mut source = verify::connect(&config.source).await?;
+    let mut dest = verify::connect(&config.dest).await?;
+    let mut report = Report {
+        source: redact_url(&config.source),
+        dest: redact_url(&config.dest),
+        sample_rate: 1.0,
+        ..Report::default()
+    };
+
+    loop {
+        let batch: Vec<SnapshotEntry> =
+            snapshot.by_ref().take(config.batch_size).collect::<io::Result<_>>().map_err(VerifyError::Snapshot)?;
+        if batch.is_empty() {
+            break;
+        }
+        report.counts.scanned += batch.len() as u64;
+        report.counts.checked += batch.len() as u64;
+        let current = read_entries(&mut source, batch.iter().map(|entry| entry.key.clone()).collect()).await?;
+        for (before, after) in batch.iter().zip(&current) {
+            match compare_entry(before, after.as_ref()) {
+                Change::Unchanged => report.counts.matched += 1,
+                Change::Expired => report.counts.vanished += 1,
+                Change::Changed(kind, detail) => {
+                    report.record(Mismatch { key: lossy(&before.key), kind, detail }, config.max_reported);
+                }
+            }
+        }
+    }
+
+    let mut cursor = 0;
+    loop {
+        let (next_cursor, keys) = scan_batch(&mut dest, cursor, config.pattern.as_deref(), config.batch_size).await?;
+        for outcome in find_unexpected(&mut source, keys).await? {
+            if let KeyOutcome::Mismatch(mismatch) = outcome {
+                report.record(mismatch, config.max_reported);
+            }
+        }
+        cursor = next_cursor;
+        if cursor == 0 {
+            break;
+        }
+    }
+
+    report.elapsed_secs = started.elapsed().as_secs_f64();
+    report.passed = report.counts.mismatches() == 0;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(type_name: &str, digest: u64, expires: bool) -> SnapshotEntry {
+        SnapshotEntry {
+            key: b"k".to_vec(),
+            type_name: type_name.to_string(),
+            digest,
+            expires,
+        }
+    }
+
+    #[test]
+    fn compare_entry_reports_lost_and_mutated_keys() {
+        let before = entry("hash", 1, false);
+        assert_eq!(compare_entry(&before, Some(&before)), Change::Unchanged);
+        assert_eq!(
+            compare_entry(&before, None),
+            Change::Changed(MismatchKind::Missing, "hash lost since snapshot".to_string())
+        );
+        assert_eq!(compare_entry(&entry("hash", 1, true), None), Change::Expired);
+        assert!(matches!(
+            compare_entry(&before, Some(&entry("set", 1, false))),
+            Change::Changed(MismatchKind::TypeMismatch, _)
+        ));
+        assert!(matches!(
+            compare_entry(&before, Some(&entry("hash", 2, false))),
+            Change::Changed(MismatchKind::ValueMismatch, _)
+        ));
+        assert!(matches!(
+            compare_entry(&before, Some(&entry("hash", 1, true))),
+            Change::Changed(MismatchKind::TtlMismatch, _)
+        ));
+    }
+
+    #[test]
+    fn snapshot_lines_round_trip_binary_keys() {
+        let entries = [
+            entry("string", u64::MAX, false),
+            SnapshotEntry { key: vec![0xff, 0x00, b'a'], ..entry("zset", 42, true) },
+        ];
+        for entry in &entries {
+            let json = serde_json::to_string(&Line::from(entry)).expect("serialize");
+            let line: Line = serde_json::from_str(&json).expect("parse");
+            assert_eq!(&SnapshotEntry::try_from(line).expect("valid line"), entry);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::mysql::{MysqlTables, is_mysql_url};
+use crate::postgres::{PostgresTables, is_postgres_url};
+use crate::table::{Column, TableSource, TableStats};
+use crate::verify::VerifyError;
+
+const POSTGRES_DEFAULT_SCHEMA: &str = "public";
+
+pub fn is_sql_url(url: &str) -> bool {
+    is_postgres_url(url) || is_mysql_url(url)
+}
+
+/// A Postgres or MySQL schema, chosen by URL scheme.
+pub enum SqlTables {
+    Postgres(PostgresTables),
+    Mysql(MysqlTables),
+}
+
+impl SqlTables {
+    /// `schema` defaults to `public` on Postgres and to the URL's database on
+    /// MySQL. `chunk_size` only applies to MySQL checksums, and `ca_cert` to
+    /// Postgres TLS.
+    pub async fn connect(url: &str, schema: Option<&str>, chunk_size: u64, ca_cert: Option<&Path>) -> Result<Self, VerifyError> {
+        if is_postgres_url(url) {
+            let schema = schema.unwrap_or(POSTGRES_DEFAULT_SCHEMA);
+            return Ok(Self::Postgres(PostgresTables::connect(url, schema, ca_cert).await?));
+        }
+        if is_mysql_url(url) {
+            return Ok(Self::Mysql(MysqlTables::connect(url, schema, chunk_size).await?));
+        }
+        Err(VerifyError::Config(format!("unsupported database URL: {url}")))
+    }
+}
+
+impl TableSource for SqlTables {
+    fn engine(&self) -> &'static str {
+        match self {
+            Self::Postgres(tables) => tables.engine(),
+            Self::Mysql(tables) => tables.engine(),
+        }
+    }
+
+    async fn tables(&mut self) -> Result<BTreeMap<String, Vec<Column>>, VerifyError> {
+        match self {
+            Self::Postgres(tables) => tables.tables().await,
+            Self::Mysql(tables) => tables.tables().await,
+        }
+    }
+
+    async fn stats(&mut self, table: &str, columns: &[Column], checksum: bool) -> Result<TableStats, VerifyError> {
+        match self {
+            Self::Postgres(tables) => tables.stats(table, columns, checksum).await,
+            Self::Mysql(tables) => tables.stats(table, columns, checksum).await,
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Instant;
+
+use eden_client::audit::redact_url;
+
+use crate::report::{Mismatch, MismatchKind, Report};
+use crate::verify::VerifyError;
+
+/// One column of a table, in ordinal order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+}
+
+/// Row count and, when requested, an order-independent content checksum.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableStats {
+    pub rows: u64,
+    pub checksum: Option<String>,
+    /// Per-range checksums, when the source checksums in primary-key chunks.
+    pub chunks: Vec<ChunkStats>,
+}
+
+/// Rows whose integer primary key falls in `[start, end)`. Chunk bounds are
+/// multiples of the chunk size, so both sides of a comparison line up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkStats {
+    pub start: i64,
+    pub end: i64,
+    pub rows: u64,
+    pub checksum: u64,
+}
+
+/// A SQL schema whose tables can be listed and summarized.
+pub trait TableSource {
+    /// Engine name. Column types and checksums are only comparable between
+    /// sources of the same engine.
+    fn engine(&self) -> &'static str;
+
+    /// Columns of every base table, keyed by table name.
+    fn tables(&mut self) -> impl Future<Output = Result<BTreeMap<String, Vec<Column>>, VerifyError>> + Send;
+
+    fn stats(&mut self, table: &str, columns: &[Column], checksum: bool) -> impl Future<Output = Result<TableStats, VerifyError>> + Send;
+}
+
+#[derive(Debug, Clone)]
+pub struct TableConfig {
+    pub source: String,
+    pub dest: String,
+    /// Tables to compare; empty compares every base table in the schema.
+    pub tables: Vec<String>,
+    /// Compare content checksums as well as row counts.
+    pub checksum: bool,
+    /// Also report tables that exist only on the destination.
+    pub bidirectional: bool,
+    pub max_reported: usize,
+}
+
+/// Compare the tables of two databases by schema, row count and optionally
+/// checksum. Mismatches are reported per table, with the table name in the
+/// `key` field of the report.
+///
+/// Between different engines only column names (case-insensitively),
+/// nullability and row counts are compared.
+pub async fn compare<S: TableSource, D: TableSource>(source: &mut S, dest: &mut D, config: &TableConfig) -> Result<Report, VerifyError> {
+    let same_engine = source.engine() == dest.engine();
+    if config.checksum && !same_engine {
+        return Err(VerifyError::Config(format!("cannot compare {} and {} checksums", source.engine(), dest.engine())));
+    }
+
+    let started = Instant::now();
+    let source_tables = source.tables().await?;
+    let dest_tables = dest.tables().await?;
+
+    let mut report = Report {
+        source: redact_url(&config.source),
+        dest: redact_url(&config.dest),
+        sample_rate: 1.0,
+        ..Report::default()
+    };
+    report.counts.scanned = source_tables.len() as u64;
+
+    let selected: Vec<&String> = if config.tables.is_empty() {
+        source_tables.keys().collect()
+    } else {
+        config.tables.iter().collect()
+    };
+
+    for table in selected {
+        report.counts.checked += 1;
+        let Some(source_columns) = source_tables.get(table) else {
+            report.record(mismatch(table, MismatchKind::Error, "table not found on source"), config.max_reported);
+            continue;
+        };
+        let Some(dest_columns) = dest_tables.get(table) else {
+            report.record(mismatch(table, MismatchKind::Missing, "table missing from dest"), config.max_reported);
+            continue;
+        };
+        if let Some(detail) = describe_schema_difference(source_columns, dest_columns, same_engine) {
+            report.record(mismatch(table, MismatchKind::TypeMismatch, &detail), config.max_reported);
+            continue;
+        }
+
+        let (source_stats, dest_stats) = tokio::join!(
+            source.stats(table, source_columns, config.checksum),
+            dest.stats(table, dest_columns, config.checksum)
+        );
+        let outcome = match (source_stats, dest_stats) {
+            (Ok(a), Ok(b)) => describe_stats_difference(&a, &b).map(|detail| mismatch(table, MismatchKind::ValueMismatch, &detail)),
+            (Err(e), _) | (_, Err(e)) => Some(mismatch(table, MismatchKind::Error, &e.to_string())),
+        };
+        match outcome {
+            Some(mismatch) => report.record(mismatch, config.max_reported),
+            None => report.counts.matched += 1,
+        }
+    }
+
+    if config.bidirectional {
+        let unexpected = dest_tables.keys().filter(|table| !source_tables.contains_key(*table));
+        for table in unexpected.filter(|table| config.tables.is_empty() || config.tables.contains(table)) {
+            report.record(mismatch(table, MismatchKind::Unexpected, "table not present on source"), config.max_reported);
+        }
+    }
+
+    report.elapsed_secs = started.elapsed().as_secs_f64();
+    report.passed = report.counts.mismatches() == 0;
+    Ok(report)
+}
+
+/// Compare column lists, returning the first difference. Without
+/// `compare_types`, names match case-insensitively and types are ignored.
+pub fn describe_schema_difference(source: &[Column], dest: &[Column], compare_types: bool) -> Option<String> {
+    let same_name = |a: &Column, b: &Column| {
+        if compare_types {
+            a.name == b.name
+        } else {
+            a.name.eq_ignore_ascii_case(&b.name)
+        }
+    };
+    for column in source {
+        let Some(other) = dest.iter().find(|other| same_name(column, other)) else {
+            return Some(format!("column {} missing from dest", column.name));
+        };
+        if compare_types && other.data_type != column.data_type {
+            return Some(format!("column {}: {} vs {}", column.name, column.data_type, other.data_type));
+        }
+        if other.nullable != column.nullable {
+            return Some(format!("column {}: nullable {} vs {}", column.name, column.nullable, other.nullable));
+        }
+    }
+    dest.iter()
+        .find(|other| !source.iter().any(|column| same_name(column, other)))
+        .map(|other| format!("unexpected column {} in dest", other.name))
+}
+
+/// Compare row counts and checksums, naming the first differing key range
+/// when both sides were checksummed in chunks.
+pub fn describe_stats_difference(source: &TableStats, dest: &TableStats) -> Option<String> {
+    if source.rows == dest.rows && source.checksum == dest.checksum {
+        return None;
+    }
+    let location = first_differing_chunk(&source.chunks, &dest.chunks)
+        .map(|(start, end)| format!(" (first difference in key range [{start}, {end}))"))
+        .unwrap_or_default();
+    if source.rows != dest.rows {
+        return Some(format!("row count {} vs {}{location}", source.rows, dest.rows));
+    }
+    Some(format!("checksum differs{location}"))
+}
+
+fn first_differing_chunk(source: &[ChunkStats], dest: &[ChunkStats]) -> Option<(i64, i64)> {
+    if source.is_empty() || dest.is_empty() {
+        return None;
+    }
+    let by_start = |chunks: &[ChunkStats]| chunks.iter().map(|chunk| (chunk.start, *chunk)).collect::<BTreeMap<_, _>>();
+    let (source, dest) = (by_start(source), by_start(dest));
+    let starts: BTreeSet<i64> = source.keys().chain(dest.keys()).copied().collect();
+    let start = starts.into_iter().find(|start| source.get(start) != dest.get(start))?;
+    source.get(&start).or_else(|| dest.get(&start)).map(|chunk| (chunk.start, chunk.end))
+}
+
+fn mismatch(table: &str, kind: MismatchKind, detail: &str) -> Mismatch {
+    Mismatch { key: table.to_string(), kind, detail: detail.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, data_type: &str) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable: true,
+        }
+    }
+
+    #[test]
+    fn schema_difference_names_the_column() {
+        let source = [column("id", "bigint"), column("email", "text")];
+        assert_eq!(describe_schema_difference(&source, &source, true), None);
+        assert_eq!(
+            describe_schema_difference(&source, &[column("id", "bigint"), column("email", "character varying")], true),
+            Some("column email: text vs character varying".to_string())
+        );
+        assert_eq!(
+            describe_schema_difference(&source, &source[..1], true),
+            Some("column email missing from dest".to_string())
+        );
+        assert_eq!(
+            describe_schema_difference(&source[..1], &source, true),
+            Some("unexpected column email in dest".to_string())
+        );
+    }
+
+    #[test]
+    fn cross_engine_schema_ignores_types_and_name_case() {
+        let source = [column("ID", "bigint(20)"), column("Email", "varchar(255)")];
+        let dest = [column("id", "bigint"), column("email", "character varying")];
+        assert_eq!(describe_schema_difference(&source, &dest, false), None);
+        assert!(describe_schema_difference(&source, &dest, true).is_some());
+    }
+
+    #[test]
+    fn stats_compare_rows_before_checksum() {
+        let stats = |rows, checksum: &str| TableStats {
+            rows,
+            checksum: Some(checksum.to_string()),
+            chunks: Vec::new(),
+        };
+        assert_eq!(describe_stats_difference(&stats(10, "a"), &stats(10, "a")), None);
+        assert_eq!(describe_stats_difference(&stats(10, "a"), &stats(9, "b")), Some("row count 10 vs 9".to_string()));
+        assert_eq!(describe_stats_difference(&stats(10, "a"), &stats(10, "b")), Some("checksum differs".to_string()));
+    }
+
+    #[test]
+    fn chunked_stats_locate_the_first_differing_range() {
+        let chunk = |start, rows, checksum| ChunkStats { start, end: start + 100, rows, checksum };
+        let stats = |rows, chunks| TableStats { rows, checksum: Some(format!("{rows}")), chunks };
+        let source = stats(150, vec![chunk(0, 100, 1), chunk(100, 50, 2)]);
+        let mut dest = stats(150, vec![chunk(0, 100, 1), chunk(100, 50, 3)]);
+        dest.checksum = Some("other".to_string());
+        assert_eq!(
+            describe_stats_difference(&source, &dest),
+            Some("checksum differs (first difference in key range [100, 200))".to_string())
+        );
+
+        let truncated = stats(100, vec![chunk(0, 100, 1)]);
+        assert_eq!(
+            describe_stats_difference(&source, &truncated),
+            Some("row count 150 vs 100 (first difference in key range [100, 200))".to_string())
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use eden_client::audit::redact_url;
+use futures::future::try_join_all;
+use redis::aio::MultiplexedConnection;
+use redis::{FromRedisValue, RedisError, RedisResult, Value};
+use redis_compare::value::{fetch_values, lossy};
+use redis_compare::{CompareOptions, compare};
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::checkpoint::{Checkpoint, Pass, Scope};
+use crate::report::{Mismatch, MismatchKind, Report};
+
+/// `PTTL` reply for a key that does not exist.
+const PTTL_NO_KEY: i64 = -2;
+
+/// How often progress is written to stderr.
+pub(crate) const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum VerifyError {
+    #[error("redis error: {0}")]
+    Redis(#[from] RedisError),
+    #[error("postgres error: {0}")]
+    Postgres(#[from] tokio_postgres::Error),
+    #[error("mysql error: {0}")]
+    Mysql(#[from] mysql_async::Error),
+    #[error("mongodb error: {0}")]
+    Mongo(#[from] mongodb::error::Error),
+    #[error("checkpoint error: {0}")]
+    Checkpoint(#[from] io::Error),
+    #[error("snapshot error: {0}")]
+    Snapshot(io::Error),
+    #[error("invalid configuration: {0}")]
+    Config(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyConfig {
+    pub source: String,
+    pub dest: String,
+    /// Optional SCAN MATCH pattern restricting the verified keyspace.
+    pub pattern: Option<String>,
+    /// SCAN COUNT hint, and the unit of work between checkpoints.
+    pub batch_size: usize,
+    /// Number of concurrent comparison pipelines per batch.
+    pub workers: usize,
+    /// Fraction of keys to compare, in (0, 1]. 1.0 verifies every key.
+    pub sample_rate: f64,
+    pub ttl_tolerance: Duration,
+    pub score_epsilon: f64,
+    /// Also scan the destination for keys the source does not have.
+    pub bidirectional: bool,
+    pub checkpoint: Option<PathBuf>,
+    pub max_reported: usize,
+}
+
+pub(crate) enum KeyOutcome {
+    Matched,
+    Vanished,
+    Mismatch(Mismatch),
+}
+
+/// Accept either a bare `host:port` or a full `redis://` / `rediss://` URL.
+pub fn redis_url(addr: &str) -> String {
+    if addr.contains("://") {
+        addr.to_string()
+    } else {
+        format!("redis://{addr}")
+    }
+}
+
+/// Deterministic per-key sampling, so repeated runs at the same rate check the
+/// same subset of keys.
+pub fn is_sampled(key: &[u8], rate: f64) -> bool {
+    rate >= 1.0 || (xxh3_64(key) as f64 / u64::MAX as f64) < rate
+}
+
+pub async fn connect(addr: &str) -> RedisResult<MultiplexedConnection> {
+    redis::Client::open(redis_url(addr))?.get_multiplexed_async_connection().await
+}
+
+/// Verify that every key on the source exists on the destination with the
+/// same type, value and TTL (within tolerance).
+pub async fn run(config: &VerifyConfig) -> Result<Report, VerifyError> {
+    validate(config)?;
+
+    let started = Instant::now();
+    let mut source = connect(&config.source).await?;
+    let mut dest = connect(&config.dest).await?;
+
+    let scope = Scope {
+        source: redact_url(&config.source),
+        dest: redact_url(&config.dest),
+        pattern: config.pattern.clone(),
+        sample_rate: config.sample_rate,
+    };
+    let resumed = match &config.checkpoint {
+        Some(path) => Checkpoint::load(path, &scope)?,
+        None => None,
+    };
+    let (mut pass, mut cursor, mut report) = match resumed {
+        Some(checkpoint) => {
+            eprintln!(
+                "eden-verify: resuming {:?} pass at cursor {} ({} keys checked so far)",
+                checkpoint.pass, checkpoint.cursor, checkpoint.report.counts.checked
+            );
+            (checkpoint.pass, checkpoint.cursor, checkpoint.report)
+        }
+        None => (
+            Pass::Source,
+            0,
+            Report {
+                source: redact_url(&config.source),
+                dest: redact_url(&config.dest),
+                sample_rate: config.sample_rate,
+                ..Report::default()
+            },
+        ),
+    };
+    let previous_elapsed = report.elapsed_secs;
+    let mut last_progress = Instant::now();
+
+    loop {
+        let scanned = match pass {
+            Pass::Source => &mut source,
+            Pass::Dest => &mut dest,
+        };
+        let (next_cursor, keys) = scan_batch(scanned, cursor, config.pattern.as_deref(), config.batch_size).await?;
+        let selected: Vec<Vec<u8>> = keys.iter().filter(|key| is_sampled(key, config.sample_rate)).cloned().collect();
+
+        let outcomes = match pass {
+            Pass::Source => {
+                report.counts.scanned += keys.len() as u64;
+                report.counts.checked += selected.len() as u64;
+                compare_keys(&source, &dest, selected, config).await?
+            }
+            Pass::Dest => find_unexpected(&mut source, selected).await?,
+        };
+        for outcome in outcomes {
+            match outcome {
+                KeyOutcome::Matched => report.counts.matched += 1,
+                KeyOutcome::Vanished => report.counts.vanished += 1,
+                KeyOutcome::Mismatch(mismatch) => report.record(mismatch, config.max_reported),
+            }
+        }
+
+        cursor = next_cursor;
+        if cursor == 0 {
+            if pass == Pass::Dest || !config.bidirectional {
+                break;
+            }
+            pass = Pass::Dest;
+        }
+
+        report.elapsed_secs = previous_elapsed + started.elapsed().as_secs_f64();
+        if let Some(path) = &config.checkpoint {
+            Checkpoint { scope: scope.clone(), pass, cursor, report: report.clone() }.save(path)?;
+        }
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            let counts = &report.counts;
+            eprintln!(
+                "  [{:.1}s] pass={pass:?} scanned={} checked={} matched={} mismatches={}",
+                report.elapsed_secs,
+                counts.scanned,
+                counts.checked,
+                counts.matched,
+                counts.mismatches()
+            );
+        }
+    }
+
+    report.elapsed_secs = previous_elapsed + started.elapsed().as_secs_f64();
+    report.passed = report.counts.mismatches() == 0;
+    if let Some(path) = &config.checkpoint {
+        Checkpoint::remove(path)?;
+    }
+    Ok(report)
+}
+
+fn validate(config: &VerifyConfig) -> Result<(), VerifyError> {
+    if !(config.sample_rate > 0.0 && config.sample_rate <= 1.0) {
+        return Err(VerifyError::Config("sample rate must be in (0, 1]".to_string()));
+    }
+    if config.batch_size == 0 || config.workers == 0 {
+        return Err(VerifyError::Config("batch size and workers must be > 0".to_string()));
+    }
+    Ok(())
+}
+
+pub(crate) async fn scan_batch(
+    conn: &mut MultiplexedConnection,
+    cursor: u64,
+    pattern: Option<&str>,
+    count: usize,
+) -> RedisResult<(u64, Vec<Vec<u8>>)> {
+    let mut cmd = redis::cmd("SCAN");
+    cmd.arg(cursor);
+    if let Some(pattern) = pattern {
+        cmd.arg("MATCH").arg(pattern);
+    }
+    cmd.arg("COUNT").arg(count);
+    cmd.query_async(conn).await
+}
+
+/// Split `keys` into `config.workers` chunks and compare them concurrently over
+/// the shared multiplexed connections.
+pub(crate) async fn compare_keys(
+    source: &MultiplexedConnection,
+    dest: &MultiplexedConnection,
+    keys: Vec<Vec<u8>>,
+    config: &VerifyConfig,
+) -> RedisResult<Vec<KeyOutcome>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let chunk_size = keys.len().div_ceil(config.workers);
+    let chunks = keys.chunks(chunk_size).map(|chunk| {
+        let mut source = source.clone();
+        let mut dest = dest.clone();
+        async move {
+            match compare_chunk(&mut source, &mut dest, chunk, config).await {
+                Ok(outcomes) => Ok::<_, RedisError>(outcomes),
+                Err(e) => Ok(chunk.iter().map(|key| mismatch(key, MismatchKind::Error, e.to_string())).collect()),
+            }
+        }
+    });
+    Ok(try_join_all(chunks).await?.into_iter().flatten().collect())
+}
+
+async fn compare_chunk(
+    source: &mut MultiplexedConnection,
+    dest: &mut MultiplexedConnection,
+    keys: &[Vec<u8>],
+    config: &VerifyConfig,
+) -> RedisResult<Vec<KeyOutcome>> {
+    let source_meta = fetch_metadata(source, keys).await?;
+    let dest_meta = fetch_metadata(dest, keys).await?;
+
+    let mut outcomes: Vec<Option<KeyOutcome>> = Vec::with_capacity(keys.len());
+    let mut to_read = Vec::new();
+    for (i, ((source_type, _), (dest_type, _))) in source_meta.iter().zip(&dest_meta).enumerate() {
+        let outcome = if source_type == "none" {
+            Some(KeyOutcome::Vanished)
+        } else if dest_type == "none" {
+            Some(mismatch(&keys[i], MismatchKind::Missing, format!("{source_type} missing from dest")))
+        } else if source_type != dest_type {
+            Some(mismatch(&keys[i], MismatchKind::TypeMismatch, format!("source {source_type}, dest {dest_type}")))
+        } else {
+            to_read.push(i);
+            None
+        };
+        outcomes.push(outcome);
+    }
+
+    let read_keys: Vec<Vec<u8>> = to_read.iter().map(|&i| keys[i].clone()).collect();
+    let read_types: Vec<String> = to_read.iter().map(|&i| source_meta[i].0.clone()).collect();
+    let source_values = fetch_values(source, &read_keys, &read_types).await?;
+    let dest_values = fetch_values(dest, &read_keys, &read_types).await?;
+
+    for ((i, source_value), dest_value) in to_read.into_iter().zip(source_values).zip(dest_values) {
+        let key = &keys[i];
+        outcomes[i] = Some(match (source_value, dest_value) {
+            (None, _) => KeyOutcome::Vanished,
+            (Some(_), None) => mismatch(key, MismatchKind::Missing, "deleted from dest during read".to_string()),
+            (Some(a), Some(b)) => {
+                let options = CompareOptions { score_epsilon: config.score_epsilon };
+                match compare(&a, &b, &options) {
+                    Some(diff) => mismatch(key, MismatchKind::ValueMismatch, diff.to_string()),
+                    None => ttl_outcome(key, source_meta[i].1, dest_meta[i].1, config.ttl_tolerance),
+                }
+            }
+        });
+    }
+
+    Ok(outcomes.into_iter().map(|outcome| outcome.unwrap_or(KeyOutcome::Vanished)).collect())
+}
+
+/// Destination pass: report keys that exist on the destination but not on the source.
+pub(crate) async fn find_unexpected(source: &mut MultiplexedConnection, keys: Vec<Vec<u8>>) -> RedisResult<Vec<KeyOutcome>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut pipe = redis::pipe();
+    for key in &keys {
+        pipe.cmd("EXISTS").arg(key);
+    }
+    let exists: Vec<bool> = pipe.query_async(source).await?;
+
+    Ok(keys
+        .iter()
+        .zip(exists)
+        .filter(|(_, exists)| !exists)
+        .map(|(key, _)| mismatch(key, MismatchKind::Unexpected, "not present on source".to_string()))
+        .collect())
+}
+
+/// `TYPE` and `PTTL` for each key, in one pipeline.
+pub(crate) async fn fetch_metadata(conn: &mut MultiplexedConnection, keys: &[Vec<u8>]) -> RedisResult<Vec<(String, i64)>> {
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.cmd("TYPE").arg(key).cmd("PTTL").arg(key);
+    }
+    let replies: Vec<Value> = pipe.query_async(conn).await?;
+    replies.chunks(2).map(|pair| Ok((String::from_redis_value(&pair[0])?, i64::from_redis_value(&pair[1])?))).collect()
+}
+
+/// Outcome for a key whose values match, from its `PTTL` on each side. A key
+/// that expired between its `TYPE` and `PTTL` replies (`-2`) has vanished
+/// rather than changed TTL.
+fn ttl_outcome(key: &[u8], source_ms: i64, dest_ms: i64, tolerance: Duration) -> KeyOutcome {
+    match (source_ms, dest_ms) {
+        (PTTL_NO_KEY, _) => KeyOutcome::Vanished,
+        // Both copies were about to expire and the destination's went first.
+        (source, PTTL_NO_KEY) if (0..=tolerance.as_millis() as i64).contains(&source) => KeyOutcome::Vanished,
+        (_, PTTL_NO_KEY) => mismatch(key, MismatchKind::Missing, "expired on dest during read".to_string()),
+        (source, dest) => match ttl_difference(source, dest, tolerance) {
+            Some(detail) => mismatch(key, MismatchKind::TtlMismatch, detail),
+            None => KeyOutcome::Matched,
+        },
+    }
+}
+
+/// Compare `PTTL` replies of existing keys: -1 means no expiry, positive
+/// values are milliseconds remaining.
+pub fn ttl_difference(source_ms: i64, dest_ms: i64, tolerance: Duration) -> Option<String> {
+    match (source_ms, dest_ms) {
+        (-1, -1) => None,
+        (-1, dest) => Some(format!("source has no TTL, dest expires in {dest}ms")),
+        (source, -1) => Some(format!("source expires in {source}ms, dest has no TTL")),
+        (source, dest) => ((source - dest).unsigned_abs() > tolerance.as_millis() as u64).then(|| format!("ttl {source}ms vs {dest}ms")),
+    }
+}
+
+fn mismatch(key: &[u8], kind: MismatchKind, detail: String) -> KeyOutcome {
+    KeyOutcome::Mismatch(Mismatch { key: lossy(key), kind, detail })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redis_url_accepts_host_port_and_urls() {
+        assert_eq!(redis_url("127.0.0.1:6379"), "redis://127.0.0.1:6379");
+        assert_eq!(redis_url("rediss://cache:6380/2"), "rediss://cache:6380/2");
+    }
+
+    #[test]
+    fn ttl_difference_respects_tolerance() {
+        let tolerance = Duration::from_secs(5);
+        assert_eq!(ttl_difference(-1, -1, tolerance), None);
+        assert_eq!(ttl_difference(60_000, 58_000, tolerance), None);
+        assert!(ttl_difference(60_000, 50_000, tolerance).is_some());
+        assert!(ttl_difference(-1, 50_000, tolerance).is_some());
+    }
+
+    #[test]
+    fn keys_that_expire_mid_compare_vanish() {
+        let tolerance = Duration::from_secs(5);
+        assert!(matches!(ttl_outcome(b"k", -2, 60_000, tolerance), KeyOutcome::Vanished));
+        assert!(matches!(ttl_outcome(b"k", 1_000, -2, tolerance), KeyOutcome::Vanished));
+        let KeyOutcome::Mismatch(missing) = ttl_outcome(b"k", -1, -2, tolerance) else {
+            panic!("a persistent source key expired on dest");
+        };
+        assert!(matches!(missing.kind, MismatchKind::Missing));
+        assert!(matches!(ttl_outcome(b"k", 60_000, 58_000, tolerance), KeyOutcome::Matched));
+    }
+
+    #[test]
+    fn sampling_is_deterministic_and_roughly_proportional() {
+        let keys: Vec<String> = (0..10_000).map(|i| format!("key:{i}")).collect();
+        let picked = keys.iter().filter(|key| is_sampled(key.as_bytes(), 0.1)).count();
+        assert!((800..1_200).contains(&picked), "picked {picked}");
+        assert!(keys.iter().all(|key| is_sampled(key.as_bytes(), 0.1) == is_sampled(key.as_bytes(), 0.1)));
+        assert!(keys.iter().all(|key| is_sampled(key.as_bytes(), 1.0)));
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use eden_client::audit::redact_url;
+use futures::StreamExt;
+use redis::aio::MultiplexedConnection;
+use redis::{Msg, RedisResult};
+use redis_compare::value::lossy;
+
+use crate::report::{Counts, Mismatch, MismatchKind, Report};
+use crate::verify::{self, KeyOutcome, VerifyConfig, VerifyError, compare_keys, is_sampled, redis_url};
+
+/// Keys waiting for their staleness window beyond this are not sampled, so a
+/// write burst cannot grow the queue without bound.
+const MAX_PENDING: usize = 100_000;
+
+#[derive(Debug, Clone)]
+pub struct WatchConfig {
+    /// Source, destination and comparison settings. `pattern` filters the
+    /// watched keys; `checkpoint` and `bidirectional` are not used.
+    pub compare: VerifyConfig,
+    /// Time allowed for a write to reach the destination before the key is compared.
+    pub staleness: Duration,
+    /// How often the consistency line is written to stderr.
+    pub report_interval: Duration,
+}
+
+/// Sample keys as they are written on the source and compare each one with
+/// the destination once `staleness` has passed, until `shutdown` completes.
+///
+/// Writes are observed through keyspace notifications, so the source must
+/// have `notify-keyspace-events` enabled with `K` and the event classes of
+/// interest, e.g. `KA`.
+pub async fn run(config: &WatchConfig, shutdown: impl Future<Output = ()>) -> Result<Report, VerifyError> {
+    let compare = &config.compare;
+    if !(compare.sample_rate > 0.0 && compare.sample_rate <= 1.0) {
+        return Err(VerifyError::Config("sample rate must be in (0, 1]".to_string()));
+    }
+    if compare.batch_size == 0 || compare.workers == 0 {
+        return Err(VerifyError::Config("batch size and workers must be > 0".to_string()));
+    }
+
+    let started = Instant::now();
+    let mut source = verify::connect(&compare.source).await?;
+    let mut dest = verify::connect(&compare.dest).await?;
+    check_notifications(&mut source).await?;
+
+    let client = redis::Client::open(redis_url(&compare.source))?;
+    let channel = keyspace_channel(&client, compare.pattern.as_deref());
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.psubscribe(channel).await?;
+    let mut events = pubsub.into_on_message();
+
+    let mut report = Report {
+        source: redact_url(&compare.source),
+        dest: redact_url(&compare.dest),
+        sample_rate: compare.sample_rate,
+        ..Report::default()
+    };
+    // Last keyspace event (e.g. `set` or `del`) of each key waiting in `queue`.
+    let mut pending: HashMap<Vec<u8>, String> = HashMap::new();
+    let mut queue: VecDeque<(Instant, Vec<u8>)> = VecDeque::new();
+    let mut dropped = 0u64;
+    let mut interval_start = (0, 0);
+    let mut ticker = tokio::time::interval(Duration::from_millis(100));
+    let mut last_report = Instant::now();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            message = events.next() => {
+                let Some(message) = message else {
+                    return Err(VerifyError::Config("source closed the keyspace notification subscription".to_string()));
+                };
+                let Some((key, event)) = parse_event(&message) else {
+                    continue;
+                };
+                report.counts.scanned += 1;
+                if !is_sampled(&key, compare.sample_rate) {
+                    continue;
+                }
+                if let Some(last_event) = pending.get_mut(&key) {
+                    // Keep the first due time so a hot key is still compared.
+                    *last_event = event;
+                } else if pending.len() >= MAX_PENDING {
+                    dropped += 1;
+                } else {
+                    queue.push_back((Instant::now() + config.staleness, key.clone()));
+                    pending.insert(key, event);
+                }
+            }
+            _ = ticker.tick() => {
+                check_due(&mut source, &mut dest, &mut pending, &mut queue, Instant::now(), &mut report, compare).await?;
+                if last_report.elapsed() >= config.report_interval {
+                    last_report = Instant::now();
+                    print_consistency(started, &report.counts, interval_start, pending.len(), dropped);
+                    interval_start = (report.counts.matched, report.counts.mismatches());
+                }
+            }
+        }
+    }
+
+    // Give keys written just before shutdown their full window.
+    if let Some(&(due, _)) = queue.back() {
+        tokio::time::sleep_until(due.into()).await;
+    }
+    check_due(&mut source, &mut dest, &mut pending, &mut queue, Instant::now(), &mut report, compare).await?;
+    print_consistency(started, &report.counts, interval_start, 0, dropped);
+
+    report.elapsed_secs = started.elapsed().as_secs_f64();
+    report.passed = report.counts.mismatches() == 0;
+    Ok(report)
+}
+
+/// Fail early when keyspace notifications are off. Servers that refuse
+/// `CONFIG GET` are assumed to be configured.
+async fn check_notifications(conn: &mut MultiplexedConnection) -> Result<(), VerifyError> {
+    let reply: redis::RedisResult<(String, String)> = redis::cmd("CONFIG").arg("GET").arg("notify-keyspace-events").query_async(conn).await;
+    match reply {
+        Ok((_, flags)) if !notifications_enabled(&flags) => Err(VerifyError::Config(format!(
+            "source notify-keyspace-events is {flags:?}; enable keyspace events, e.g. CONFIG SET notify-keyspace-events KA"
+        ))),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            eprintln!("eden-verify: cannot read notify-keyspace-events ({e}); assuming keyspace events are enabled");
+            Ok(())
+        }
+    }
+}
+
+/// Keyspace (`K`) events must be on, along with at least one event class.
+fn notifications_enabled(flags: &str) -> bool {
+    flags.contains('K') && flags.chars().any(|flag| flag != 'K' && flag != 'E')
+}
+
+/// Keyspace notification pattern for `pattern` in the database the client
+/// selects. Comparisons read that database, so writes to others are not
+/// watched.
+fn keyspace_channel(client: &redis::Client, pattern: Option<&str>) -> String {
+    format!("__keyspace@{}__:{}", client.get_connection_info().redis.db, pattern.unwrap_or("*"))
+}
+
+/// Key and event of a `__keyspace@<db>__:<key>` message.
+fn parse_event(message: &Msg) -> Option<(Vec<u8>, String)> {
+    let channel: Vec<u8> = message.get_channel().ok()?;
+    let start = channel.windows(3).position(|window| window == b"__:")? + 3;
+    let event: String = message.get_payload().ok()?;
+    Some((channel[start..].to_vec(), event))
+}
+
+/// Compare every queued key whose staleness window ended by `now`.
+async fn check_due(
+    source: &mut MultiplexedConnection,
+    dest: &mut MultiplexedConnection,
+    pending: &mut HashMap<Vec<u8>, String>,
+    queue: &mut VecDeque<(Instant, Vec<u8>)>,
+    now: Instant,
+    report: &mut Report,
+    config: &VerifyConfig,
+) -> Result<(), VerifyError> {
+    while queue.front().is_some_and(|(due, _)| *due <= now) {
+        let mut keys = Vec::new();
+        let mut deleted = HashSet::new();
+        while keys.len() < config.batch_size && queue.front().is_some_and(|(due, _)| *due <= now) {
+            let (_, key) = queue.pop_front().expect("front checked above");
+            if pending.remove(&key).is_some_and(|event| event == "del") {
+                deleted.insert(key.clone());
+            }
+            keys.push(key);
+        }
+
+        report.counts.checked += keys.len() as u64;
+        let outcomes = compare_keys(source, dest, keys.clone(), config).await?;
+        let mut vanished_deletes = Vec::new();
+        for (key, outcome) in keys.into_iter().zip(outcomes) {
+            match outcome {
+                KeyOutcome::Matched => report.counts.matched += 1,
+                // A key deleted on the source must be gone from the destination too.
+                KeyOutcome::Vanished if deleted.contains(&key) => vanished_deletes.push(key),
+                KeyOutcome::Vanished => report.counts.vanished += 1,
+                KeyOutcome::Mismatch(mismatch) => report.record(mismatch, config.max_reported),
+            }
+        }
+        let deletes = vanished_deletes.len() as u64;
+        let missed = undeleted_on_dest(dest, vanished_deletes).await?;
+        report.counts.matched += deletes - missed.len() as u64;
+        for key in missed {
+            let mismatch = Mismatch {
+                key: lossy(&key),
+                kind: MismatchKind::Unexpected,
+                detail: "deleted on source but still on dest".to_string(),
+            };
+            report.record(mismatch, config.max_reported);
+        }
+    }
+    Ok(())
+}
+
+/// Keys deleted on the source that still exist on the destination.
+async fn undeleted_on_dest(dest: &mut MultiplexedConnection, keys: Vec<Vec<u8>>) -> RedisResult<Vec<Vec<u8>>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut pipe = redis::pipe();
+    for key in &keys {
+        pipe.cmd("EXISTS").arg(key);
+    }
+    let exists: Vec<bool> = pipe.query_async(dest).await?;
+    Ok(still_present(keys, exists))
+}
+
+/// The keys whose `EXISTS` reply is true.
+fn still_present(keys: Vec<Vec<u8>>, exists: Vec<bool>) -> Vec<Vec<u8>> {
+    keys.into_iter().zip(exists).filter_map(|(key, exists)| exists.then_some(key)).collect()
+}
+
+/// Share of compared keys that matched, as a percentage. `None` before any
+/// key has been compared.
+pub fn consistency(matched: u64, mismatches: u64) -> Option<f64> {
+    let compared = matched + mismatches;
+    (compared > 0).then(|| 100.0 * matched as f64 / compared as f64)
+}
+
+/// `interval_start` holds the matched and mismatch counts at the previous report.
+fn print_consistency(started: Instant, total: &Counts, interval_start: (u64, u64), pending: usize, dropped: u64) {
+    let percent = |matched, mismatches| consistency(matched, mismatches).map_or_else(|| "-".to_string(), |p| format!("{p:.2}%"));
+    eprintln!(
+        "  [{:.1}s] writes={} checked={} matched={} mismatches={} pending={pending} dropped={dropped} consistency={} (interval {})",
+        started.elapsed().as_secs_f64(),
+        total.scanned,
+        total.checked,
+        total.matched,
+        total.mismatches(),
+        percent(total.matched, total.mismatches()),
+        percent(total.matched - interval_start.0, total.mismatches() - interval_start.1),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notifications_need_keyspace_events_and_a_class() {
+        assert!(notifications_enabled("KA"));
+        assert!(notifications_enabled("Kg$"));
+        assert!(!notifications_enabled(""));
+        assert!(!notifications_enabled("EA"));
+        assert!(!notifications_enabled("K"));
+    }
+
+    #[test]
+    fn keyspace_channel_is_limited_to_the_url_database() {
+        let client = |addr: &str| redis::Client::open(redis_url(addr)).expect("valid URL");
+        assert_eq!(keyspace_channel(&client("127.0.0.1:6379"), None), "__keyspace@0__:*");
+        assert_eq!(keyspace_channel(&client("redis://cache:6379/3"), Some("user:*")), "__keyspace@3__:user:*");
+    }
+
+    #[test]
+    fn consistency_is_share_of_compared_keys() {
+        assert_eq!(consistency(0, 0), None);
+        assert_eq!(consistency(99, 1), Some(99.0));
+        assert_eq!(consistency(0, 3), Some(0.0));
+    }
+
+    #[test]
+    fn only_deletes_missing_from_dest_are_reported() {
+        let keys = vec![b"applied".to_vec(), b"missed".to_vec()];
+        assert_eq!(still_present(keys, vec![false, true]), [b"missed".to_vec()]);
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::{Number, Value as Json};
+
+use crate::value::{RedisValue, lossy};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompareOptions {
+    /// Sorted-set scores are equal when they differ by at most this much,
+    /// which absorbs float formatting differences between Redis versions.
+    pub score_epsilon: f64,
+}
+
+/// Part of a collection named in a [`ValueDiff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Element {
+    /// A hash field.
+    Field,
+    /// A set or sorted-set member.
+    Member,
+    /// A stream entry, named by its ID.
+    Entry,
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Element::Field => "field",
+            Element::Member => "member",
+            Element::Entry => "entry",
+        })
+    }
+}
+
+/// The first difference between a source and a destination value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "diff", rename_all = "snake_case")]
+pub enum ValueDiff {
+    /// The values are of different types.
+    Kind {
+        source: &'static str,
+        dest: &'static str,
+    },
+    /// Strings or `DUMP` payloads differ.
+    Bytes {
+        source_len: usize,
+        dest_len: usize,
+    },
+    Missing {
+        element: Element,
+        name: String,
+    },
+    Unexpected {
+        element: Element,
+        name: String,
+    },
+    /// A field's value, a member's score or a stream entry differs.
+    Changed {
+        element: Element,
+        name: String,
+    },
+    /// The list element at `index` differs.
+    Index {
+        index: usize,
+    },
+    /// Lists or streams agree on their common prefix but not in length.
+    Length {
+        source: usize,
+        dest: usize,
+    },
+    /// JSON documents first differ at `path`, e.g. `$.items[2].price`.
+    Json {
+        path: String,
+    },
+}
+
+impl fmt::Display for ValueDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueDiff::Kind { source, dest } => write!(f, "value kinds differ (source {source}, dest {dest})"),
+            ValueDiff::Bytes { source_len, dest_len } => write!(f, "value differs ({source_len} vs {dest_len} bytes)"),
+            ValueDiff::Missing { element, name } => write!(f, "{element} {name} missing from dest"),
+            ValueDiff::Unexpected { element, name } => write!(f, "unexpected {element} {name} in dest"),
+            ValueDiff::Changed { element, name } => write!(f, "{element} {name} differs"),
+            ValueDiff::Index { index } => write!(f, "element {index} differs"),
+            ValueDiff::Length { source, dest } => write!(f, "length {source} vs {dest}"),
+            ValueDiff::Json { path } => write!(f, "JSON differs at {path}"),
+        }
+    }
+}
+
+/// Compare two values, returning their first difference.
+pub fn compare(source: &RedisValue, dest: &RedisValue, options: &CompareOptions) -> Option<ValueDiff> {
+    match (source, dest) {
+        (RedisValue::String(a), RedisValue::String(b)) | (RedisValue::Opaque(a), RedisValue::Opaque(b)) => {
+            (a != b).then_some(ValueDiff::Bytes { source_len: a.len(), dest_len: b.len() })
+        }
+        (RedisValue::Hash(a), RedisValue::Hash(b)) => diff_maps(a, b, |x, y| x == y, Element::Field),
+        (RedisValue::ZSet(a), RedisValue::ZSet(b)) => {
+            // Equal infinities subtract to NaN, so compare them directly.
+            diff_maps(a, b, |x, y| x == y || (x - y).abs() <= options.score_epsilon, Element::Member)
+        }
+        (RedisValue::Set(a), RedisValue::Set(b)) => {
+            if let Some(member) = a.difference(b).next() {
+                return Some(ValueDiff::Missing { element: Element::Member, name: lossy(member) });
+            }
+            b.difference(a).next().map(|member| ValueDiff::Unexpected { element: Element::Member, name: lossy(member) })
+        }
+        (RedisValue::List(a), RedisValue::List(b)) => {
+            if let Some(index) = a.iter().zip(b).position(|(x, y)| x != y) {
+                return Some(ValueDiff::Index { index });
+            }
+            (a.len() != b.len()).then_some(ValueDiff::Length { source: a.len(), dest: b.len() })
+        }
+        (RedisValue::Stream(a), RedisValue::Stream(b)) => {
+            if let Some(((id, _), _)) = a.iter().zip(b).find(|(x, y)| x != y) {
+                return Some(ValueDiff::Changed { element: Element::Entry, name: id.clone() });
+            }
+            (a.len() != b.len()).then_some(ValueDiff::Length { source: a.len(), dest: b.len() })
+        }
+        (RedisValue::Json(a), RedisValue::Json(b)) => json_diff(a, b).map(|path| ValueDiff::Json { path: render_path(&path) }),
+        _ => Some(ValueDiff::Kind { source: source.kind(), dest: dest.kind() }),
+    }
+}
+
+fn diff_maps<V>(a: &BTreeMap<Vec<u8>, V>, b: &BTreeMap<Vec<u8>, V>, eq: impl Fn(&V, &V) -> bool, element: Element) -> Option<ValueDiff> {
+    for (name, value) in a {
+        let Some(other) = b.get(name) else {
+            return Some(ValueDiff::Missing { element, name: lossy(name) });
+        };
+        if !eq(value, other) {
+            return Some(ValueDiff::Changed { element, name: lossy(name) });
+        }
+    }
+    b.keys().find(|name| !a.contains_key(*name)).map(|name| ValueDiff::Unexpected { element, name: lossy(name) })
+}
+
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Path to the first difference, innermost segment first. Object keys are
+/// compared regardless of order, and numbers by value, so `1` equals `1.0`.
+fn json_diff<'a>(a: &'a Json, b: &'a Json) -> Option<Vec<Segment<'a>>> {
+    match (a, b) {
+        (Json::Object(x), Json::Object(y)) => {
+            for (key, value) in x {
+                let inner = match y.get(key) {
+                    Some(other) => json_diff(value, other),
+                    None => Some(Vec::new()),
+                };
+                if let Some(mut path) = inner {
+                    path.push(Segment::Key(key));
+                    return Some(path);
+                }
+            }
+            let key = y.keys().find(|key| !x.contains_key(*key))?;
+            Some(vec![Segment::Key(key)])
+        }
+        (Json::Array(x), Json::Array(y)) => {
+            for (index, (value, other)) in x.iter().zip(y).enumerate() {
+                if let Some(mut path) = json_diff(value, other) {
+                    path.push(Segment::Index(index));
+                    return Some(path);
+                }
+            }
+            (x.len() != y.len()).then(|| vec![Segment::Index(x.len().min(y.len()))])
+        }
+        (Json::Number(x), Json::Number(y)) => (!numbers_equal(x, y)).then(Vec::new),
+        _ => (a != b).then(Vec::new),
+    }
+}
+
+fn numbers_equal(a: &Number, b: &Number) -> bool {
+    if a.is_f64() || b.is_f64() {
+        return a.as_f64() == b.as_f64();
+    }
+    a == b
+}
+
+fn render_path(path: &[Segment]) -> String {
+    let mut rendered = String::from("$");
+    for segment in path.iter().rev() {
+        match segment {
+            Segment::Key(key) => {
+                rendered.push('.');
+                rendered.push_str(key);
+            }
+            Segment::Index(index) => rendered.push_str(&format!("[{index}]")),
+        }
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn differs(a: &RedisValue, b: &RedisValue, score_epsilon: f64) -> Option<String> {
+        compare(a, b, &CompareOptions { score_epsilon }).map(|diff| diff.to_string())
+    }
+
+    #[test]
+    fn zset_scores_within_epsilon_match() {
+        let a = RedisValue::ZSet(BTreeMap::from([(b"m".to_vec(), 1.0)]));
+        let b = RedisValue::ZSet(BTreeMap::from([(b"m".to_vec(), 1.0 + 1e-12)]));
+        assert_eq!(differs(&a, &b, 1e-9), None);
+        assert_eq!(differs(&a, &b, 0.0), Some("member m differs".to_string()));
+
+        let infinite = RedisValue::ZSet(BTreeMap::from([(b"lo".to_vec(), f64::NEG_INFINITY), (b"hi".to_vec(), f64::INFINITY)]));
+        assert_eq!(differs(&infinite, &infinite, 1e-9), None);
+        let finite = RedisValue::ZSet(BTreeMap::from([(b"lo".to_vec(), f64::NEG_INFINITY), (b"hi".to_vec(), f64::MAX)]));
+        assert_eq!(differs(&infinite, &finite, 1e-9), Some("member hi differs".to_string()));
+    }
+
+    #[test]
+    fn hash_reports_missing_and_unexpected_fields() {
+        let a = RedisValue::Hash(BTreeMap::from([(b"f1".to_vec(), b"v".to_vec())]));
+        let b = RedisValue::Hash(BTreeMap::from([(b"f2".to_vec(), b"v".to_vec())]));
+        assert_eq!(
+            compare(&a, &b, &CompareOptions::default()),
+            Some(ValueDiff::Missing { element: Element::Field, name: "f1".to_string() })
+        );
+        assert_eq!(differs(&b, &a, 0.0), Some("field f2 missing from dest".to_string()));
+        assert_eq!(differs(&b, &b, 0.0), None);
+    }
+
+    #[test]
+    fn list_reports_first_differing_index_and_length() {
+        let a = RedisValue::List(vec![b"a".to_vec(), b"b".to_vec()]);
+        let b = RedisValue::List(vec![b"a".to_vec(), b"c".to_vec()]);
+        let c = RedisValue::List(vec![b"a".to_vec()]);
+        assert_eq!(differs(&a, &b, 0.0), Some("element 1 differs".to_string()));
+        assert_eq!(differs(&a, &c, 0.0), Some("length 2 vs 1".to_string()));
+        assert_eq!(
+            differs(&a, &RedisValue::String(Vec::new()), 0.0),
+            Some("value kinds differ (source list, dest string)".to_string())
+        );
+    }
+
+    #[test]
+    fn json_reports_the_path_of_the_first_difference() {
+        let json = |value: Json| RedisValue::Json(value);
+        let a = json(json!({ "id": 7, "items": [{ "sku": "a", "price": 1.5 }, { "sku": "b" }] }));
+        let reordered = json(json!({ "items": [{ "price": 1.5, "sku": "a" }, { "sku": "b" }], "id": 7.0 }));
+        assert_eq!(differs(&a, &reordered, 0.0), None);
+
+        let repriced = json(json!({ "id": 7, "items": [{ "sku": "a", "price": 2 }, { "sku": "b" }] }));
+        assert_eq!(differs(&a, &repriced, 0.0), Some("JSON differs at $.items[0].price".to_string()));
+        let shorter = json(json!({ "id": 7, "items": [{ "sku": "a", "price": 1.5 }] }));
+        assert_eq!(differs(&a, &shorter, 0.0), Some("JSON differs at $.items[1]".to_string()));
+        assert_eq!(differs(&json(json!(1)), &json(json!("1")), 0.0), Some("JSON differs at $".to_string()));
+    }
+}
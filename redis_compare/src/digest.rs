@@ -0,0 +1,136 @@
+use serde_json::Value as Json;
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::value::RedisValue;
+
+/// Integers below this magnitude convert to `f64` without loss.
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Digest of a value, used by snapshots to detect changes without storing
+/// the data. Collections hash in their canonical order, so equal values
+/// digest equally; sorted-set scores are hashed exactly.
+pub fn digest(value: &RedisValue) -> u64 {
+    let mut hasher = Xxh3::new();
+    match value {
+        RedisValue::String(bytes) => {
+            put(&mut hasher, b"string");
+            put(&mut hasher, bytes);
+        }
+        RedisValue::Hash(fields) => {
+            put(&mut hasher, b"hash");
+            for (field, value) in fields {
+                put(&mut hasher, field);
+                put(&mut hasher, value);
+            }
+        }
+        RedisValue::List(elements) => {
+            put(&mut hasher, b"list");
+            elements.iter().for_each(|element| put(&mut hasher, element));
+        }
+        RedisValue::Set(members) => {
+            put(&mut hasher, b"set");
+            members.iter().for_each(|member| put(&mut hasher, member));
+        }
+        RedisValue::ZSet(members) => {
+            put(&mut hasher, b"zset");
+            for (member, score) in members {
+                put(&mut hasher, member);
+                hasher.update(&score.to_bits().to_le_bytes());
+            }
+        }
+        RedisValue::Stream(entries) => {
+            put(&mut hasher, b"stream");
+            for (id, fields) in entries {
+                put(&mut hasher, id.as_bytes());
+                hasher.update(&(fields.len() as u64).to_le_bytes());
+                for (field, value) in fields {
+                    put(&mut hasher, field);
+                    put(&mut hasher, value);
+                }
+            }
+        }
+        RedisValue::Json(document) => {
+            put(&mut hasher, b"json");
+            put_json(&mut hasher, document);
+        }
+        RedisValue::Opaque(payload) => {
+            put(&mut hasher, b"opaque");
+            put(&mut hasher, payload);
+        }
+    }
+    hasher.digest()
+}
+
+/// Hash a JSON document with object keys in sorted order and numbers by
+/// value, matching how [`compare`](crate::compare) treats them. Each value
+/// starts with a tag byte for its type, so `null` and `"null"`, or `[]` and
+/// `{}`, digest differently.
+fn put_json(hasher: &mut Xxh3, value: &Json) {
+    match value {
+        Json::Null => hasher.update(&[0]),
+        Json::Bool(flag) => hasher.update(&[1, u8::from(*flag)]),
+        Json::Number(number) => {
+            hasher.update(&[2]);
+            // Integers hash as the float they equal unless f64 cannot hold them exactly.
+            match number.as_f64() {
+                Some(float) if number.is_f64() || float.abs() < MAX_EXACT_INTEGER => hasher.update(&float.to_bits().to_le_bytes()),
+                _ => put(hasher, number.to_string().as_bytes()),
+            }
+        }
+        Json::String(text) => {
+            hasher.update(&[3]);
+            put(hasher, text.as_bytes());
+        }
+        Json::Array(elements) => {
+            hasher.update(&[4]);
+            hasher.update(&(elements.len() as u64).to_le_bytes());
+            elements.iter().for_each(|element| put_json(hasher, element));
+        }
+        Json::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            hasher.update(&[5]);
+            hasher.update(&(keys.len() as u64).to_le_bytes());
+            for key in keys {
+                put(hasher, key.as_bytes());
+                put_json(hasher, &fields[key]);
+            }
+        }
+    }
+}
+
+/// Length-prefix each element so adjacent elements cannot run together.
+fn put(hasher: &mut Xxh3, bytes: &[u8]) {
+    hasher.update(&(bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_ignores_encoding_but_not_content() {
+        let a = RedisValue::List(vec![b"ab".to_vec(), b"c".to_vec()]);
+        let b = RedisValue::List(vec![b"a".to_vec(), b"bc".to_vec()]);
+        assert_ne!(digest(&a), digest(&b));
+        let set = |members: &[&str]| RedisValue::Set(members.iter().map(|m| m.as_bytes().to_vec()).collect());
+        assert_eq!(digest(&set(&["x", "y"])), digest(&set(&["y", "x"])));
+        assert_ne!(digest(&set(&["x"])), digest(&RedisValue::List(vec![b"x".to_vec()])));
+    }
+
+    #[test]
+    fn json_digest_ignores_key_order_and_number_form() {
+        let json = |text: &str| RedisValue::Json(serde_json::from_str(text).expect("valid JSON"));
+        assert_eq!(digest(&json(r#"{"a":1,"b":[true,null]}"#)), digest(&json(r#"{"b":[true,null],"a":1.0}"#)));
+        for (a, b) in [
+            ("1", r#""1""#),
+            ("null", r#""null""#),
+            ("true", r#""true""#),
+            ("[]", "{}"),
+            ("[null]", "[false]"),
+        ] {
+            assert_ne!(digest(&json(a)), digest(&json(b)), "{a} vs {b}");
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! Redis values read in a canonical form and compared across instances.
+//!
+//! - [`value`] - [`RedisValue`] and the commands that read and decode it
+//! - [`diff`] - [`compare`] and the [`ValueDiff`] it returns
+//! - [`digest`] - order-independent hashes for change detection
+
+pub mod diff;
+pub mod digest;
+pub mod value;
+
+pub use diff::{CompareOptions, ValueDiff, compare};
+pub use value::RedisValue;
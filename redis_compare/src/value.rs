@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use redis::aio::MultiplexedConnection;
+use redis::{ErrorKind, FromRedisValue, RedisError, RedisResult, Value};
+
+/// `TYPE` reported for RedisJSON documents.
+pub const JSON_TYPE: &str = "ReJSON-RL";
+
+/// A single stream entry: its ID and field/value pairs in insertion order.
+pub type StreamEntry = (String, Vec<(Vec<u8>, Vec<u8>)>);
+
+/// A Redis value read in a form that can be compared across instances.
+///
+/// Collections whose element order is not observable (hashes, sets, sorted-set
+/// members, JSON object keys) are stored so that two instances holding the
+/// same data compare equal regardless of internal encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedisValue {
+    String(Vec<u8>),
+    Hash(BTreeMap<Vec<u8>, Vec<u8>>),
+    List(Vec<Vec<u8>>),
+    Set(BTreeSet<Vec<u8>>),
+    ZSet(BTreeMap<Vec<u8>, f64>),
+    Stream(Vec<StreamEntry>),
+    /// A RedisJSON document.
+    Json(serde_json::Value),
+    /// Module and unknown types, compared through their `DUMP` payload.
+    Opaque(Vec<u8>),
+}
+
+impl RedisValue {
+    /// Redis type name of the value, as `TYPE` reports it; `opaque` for
+    /// values read through `DUMP`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RedisValue::String(_) => "string",
+            RedisValue::Hash(_) => "hash",
+            RedisValue::List(_) => "list",
+            RedisValue::Set(_) => "set",
+            RedisValue::ZSet(_) => "zset",
+            RedisValue::Stream(_) => "stream",
+            RedisValue::Json(_) => JSON_TYPE,
+            RedisValue::Opaque(_) => "opaque",
+        }
+    }
+}
+
+/// Build the command that reads a whole value of the given Redis `TYPE`.
+pub fn read_command(type_name: &str, key: &[u8]) -> redis::Cmd {
+    let mut cmd = match type_name {
+        "string" => redis::cmd("GET"),
+        "hash" => redis::cmd("HGETALL"),
+        "list" => {
+            let mut cmd = redis::cmd("LRANGE");
+            cmd.arg(key).arg(0).arg(-1);
+            return cmd;
+        }
+        "set" => redis::cmd("SMEMBERS"),
+        "zset" => {
+            let mut cmd = redis::cmd("ZRANGE");
+            cmd.arg(key).arg(0).arg(-1).arg("WITHSCORES");
+            return cmd;
+        }
+        "stream" => {
+            let mut cmd = redis::cmd("XRANGE");
+            cmd.arg(key).arg("-").arg("+");
+            return cmd;
+        }
+        JSON_TYPE => redis::cmd("JSON.GET"),
+        _ => redis::cmd("DUMP"),
+    };
+    cmd.arg(key);
+    cmd
+}
+
+/// Decode the reply of [`read_command`] for the given Redis `TYPE`.
+pub fn decode(type_name: &str, reply: &Value) -> RedisResult<RedisValue> {
+    Ok(match type_name {
+        "string" => RedisValue::String(Vec::<u8>::from_redis_value(reply)?),
+        "hash" => RedisValue::Hash(BTreeMap::<Vec<u8>, Vec<u8>>::from_redis_value(reply)?),
+        "list" => RedisValue::List(Vec::<Vec<u8>>::from_redis_value(reply)?),
+        "set" => RedisValue::Set(BTreeSet::<Vec<u8>>::from_redis_value(reply)?),
+        "zset" => RedisValue::ZSet(Vec::<(Vec<u8>, f64)>::from_redis_value(reply)?.into_iter().collect()),
+        "stream" => {
+            let entries = Vec::<(String, Vec<Vec<u8>>)>::from_redis_value(reply)?;
+            RedisValue::Stream(entries.into_iter().map(|(id, fields)| (id, pair_up(fields))).collect())
+        }
+        JSON_TYPE => {
+            let text = String::from_redis_value(reply)?;
+            let json = serde_json::from_str(&text)
+                .map_err(|e| RedisError::from((ErrorKind::TypeError, "JSON.GET returned invalid JSON", e.to_string())))?;
+            RedisValue::Json(json)
+        }
+        _ => RedisValue::Opaque(Vec::<u8>::from_redis_value(reply)?),
+    })
+}
+
+fn pair_up(flat: Vec<Vec<u8>>) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut pairs = Vec::with_capacity(flat.len() / 2);
+    let mut iter = flat.into_iter();
+    while let (Some(field), Some(value)) = (iter.next(), iter.next()) {
+        pairs.push((field, value));
+    }
+    pairs
+}
+
+/// Read the values of `keys` in one pipeline. `types` holds the `TYPE` of each
+/// key as observed earlier; keys that disappear in between decode as `None`.
+pub async fn fetch_values(conn: &mut MultiplexedConnection, keys: &[Vec<u8>], types: &[String]) -> RedisResult<Vec<Option<RedisValue>>> {
+    let mut pipe = redis::pipe();
+    for (key, type_name) in keys.iter().zip(types) {
+        pipe.add_command(read_command(type_name, key));
+    }
+    let replies: Vec<Value> = pipe.query_async(conn).await?;
+
+    replies
+        .iter()
+        .zip(types)
+        .map(|(reply, type_name)| match reply {
+            Value::Nil => Ok(None),
+            reply => decode(type_name, reply).map(Some),
+        })
+        .collect()
+}
+
+pub fn lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(s: &str) -> Value {
+        Value::BulkString(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn decodes_stream_entries_into_field_pairs() {
+        let reply = Value::Array(vec![Value::Array(vec![
+            bulk("1-0"),
+            Value::Array(vec![bulk("f"), bulk("v"), bulk("g"), bulk("w")]),
+        ])]);
+        let decoded = decode("stream", &reply).expect("decode stream");
+        assert_eq!(
+            decoded,
+            RedisValue::Stream(vec![("1-0".to_string(), vec![(b"f".to_vec(), b"v".to_vec()), (b"g".to_vec(), b"w".to_vec())])])
+        );
+    }
+
+    #[test]
+    fn decodes_json_documents() {
+        let decoded = decode(JSON_TYPE, &bulk(r#"{"b":[1,2],"a":null}"#)).expect("decode JSON");
+        assert_eq!(decoded, RedisValue::Json(serde_json::json!({ "a": null, "b": [1, 2] })));
+        assert!(decode(JSON_TYPE, &bulk("{")).is_err());
+    }
+}